@@ -1,8 +1,14 @@
 use axum::body::Body;
 use futures::TryStreamExt;
-use std::io::ErrorKind;
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
 use tokio_util::io::{ReaderStream, StreamReader};
-use crate::{html::html_text_extractor::HtmlTextExtractor, text_extractor::{OutputFormat, TextExtractor}};
+use crate::{
+    error::Error,
+    html::{html_text_extractor::HtmlTextExtractor, profile::Profile},
+    text_extractor::{ExtractParameters, InputFormat, TextExtractor},
+    util::{format_detector::{FormatDetector, SNIFF_LENGTH}, json_writer::JsonWriter},
+    xml::{feed_text_extractor::FeedTextExtractor, xml_text_extractor::XmlTextExtractor}
+};
 
 // Implements text extraction from a Body object to other one. (Both HTTP request and response contains bodies.)
 pub struct BodyTextExtractor {}
@@ -10,7 +16,7 @@ pub struct BodyTextExtractor {}
 impl BodyTextExtractor {
 
     // Extracts text from a body object and returns it in another body object
-    pub async fn extract(request_body: Body, output_format: OutputFormat) -> Body    
+    pub async fn extract(request_body: Body, mut parameters: ExtractParameters, profile: Option<Profile>) -> Body
     {
         let stream = request_body.into_data_stream();
 
@@ -20,15 +26,42 @@ impl BodyTextExtractor {
         // Text extraction and returning the response must happen in parallel
         tokio::spawn(async move {
             let mut reader =
-                StreamReader::new(stream.map_err(|e| std::io::Error::new(ErrorKind::Other, e)));            
-            let result = HtmlTextExtractor {}.extract(&mut reader, &mut input, output_format).await;            
+                StreamReader::new(stream.map_err(std::io::Error::other));
+
+            // The beginning of the body is read to detect its format, then it is extracted together with the rest
+            let mut head = Vec::with_capacity(SNIFF_LENGTH);
+            let result = match (&mut reader).take(SNIFF_LENGTH as u64).read_to_end(&mut head).await {
+                Ok(_) => {
+                    let input_format = parameters.input_format.unwrap_or_else(|| FormatDetector::detect(parameters.content_type.as_deref(), &head));
+                    parameters.input_format = Some(input_format);
+                    let mut reader = head.as_slice().chain(reader);
+                    match input_format {
+                        InputFormat::Html => HtmlTextExtractor::with_profile(profile).extract(&mut reader, &mut input, &parameters).await,
+                        InputFormat::Xml => XmlTextExtractor::new().extract(&mut reader, &mut input, &parameters).await,
+                        InputFormat::Feed => FeedTextExtractor::new().extract(&mut reader, &mut input, &parameters).await,
+                        InputFormat::Pdf | InputFormat::Zip | InputFormat::Gzip => {
+                            BodyTextExtractor::write_unsupported(&mut input, &parameters, input_format).await
+                        }
+                    }
+                }
+                Err(error) => Err(Error::from(error)),
+            };
 
             if let Err(error) = result {
                 println!("An error occured while pocessing the reuqest: {}", error.message);
             }
         });
-        
+
         // Response body will read the extracted text from the output stream to return in the response
         Body::from_stream(ReaderStream::new(output))
     }
+
+    // Formats without a text extractor are reported in the error of the output
+    async fn write_unsupported<W: AsyncWriteExt + Unpin>(writer: &mut W, parameters: &ExtractParameters, input_format: InputFormat) -> Result<(), Error> {
+        let mut output = JsonWriter::new(parameters);
+        output.start();
+        output.end(&format!("Unsupported input format: {}", JsonWriter::to_json(&input_format).trim_matches('"')));
+        writer.write_all(output.take_output().as_bytes()).await?;
+        Ok(())
+    }
 }
@@ -63,14 +63,7 @@ impl ContentParser {
                             EntityParser::parse(context).await?;
                             context.buffer.clear();
                         }
-                        ch => {
-                            // Whitespaces in HTML source are usually not displayed on the page except one space between words.
-                            if ch.is_whitespace() {
-                                context.write(' ').await?;
-                            } else {
-                                context.write(ch).await?;
-                            }
-                        }
+                        ch => context.write_text(ch).await?,
                     }
                 }
                 Err(error) => return if error.eof && is_root_level { Ok(None) } else { println!("Root level: {}", is_root_level); Err(error) },
@@ -0,0 +1,298 @@
+use super::{
+    element::Element,
+    element_handler::{ElementHandler, HandlerContext},
+    list_handler::ListHandler,
+    profile::{Profile, ProfileHandler},
+    selector::SelectorList,
+    table_handler::TableHandler,
+};
+use crate::{error::Error, text_extractor::{ExtractParameters, OutputFormat, TableFormat}};
+
+// Create the handlers implementing the default behaviour of the extractor, customized by the rules of a profile
+pub fn default_handlers(parameters: &ExtractParameters, profile: Option<&Profile>) -> Result<Vec<Box<dyn ElementHandler>>, Error> {
+    let mut handlers: Vec<Box<dyn ElementHandler>> = vec![
+        Box::new(StructureHandler {}),
+        Box::new(SelectorHandler::new(parameters, profile)?),
+    ];
+    handlers.push(Box::new(HiddenContentHandler { hidden_elements: 0 }));
+    handlers.push(Box::new(PreformattedHandler { skip_newline: false }));
+    if parameters.detect_language || parameters.stats {
+        handlers.push(Box::new(LanguageHandler {}));
+    }
+    if parameters.output_format == OutputFormat::Advanced {
+        handlers.push(Box::new(SpanHandler {}));
+    }
+    if parameters.list_markers {
+        handlers.push(Box::new(ListHandler::default()));
+    }
+    if parameters.tables || parameters.table_format != TableFormat::Text {
+        handlers.push(Box::new(TableHandler::default()));
+    }
+    if let Some(profile) = profile {
+        handlers.push(Box::new(ProfileHandler::new(profile)?));
+    }
+    Ok(handlers)
+}
+
+// Handler deciding from elements to include contents in the output and when extra new line characters are needed based on element names
+pub struct StructureHandler {}
+
+impl ElementHandler for StructureHandler {
+    fn on_start_element(&mut self, element: &Element, context: &mut HandlerContext) -> Option<bool> {
+        match element.name.as_str() {
+            // Elements to include in the output
+            "body" => return Some(true),
+            "title" => {
+                context.output.start_title();
+                return Some(true);
+            }
+            // Elements to exclude from the output
+            "script" | "style" => return Some(false),
+            // Elements needing extra new line characters in the output
+            // (Tables rendered as one text block contain their own line breaks.)
+            "br" | "td" | "th"
+                if context.parameters.table_format == TableFormat::Text || !context.elements.iter().any(|element| element.name == "table") =>
+            {
+                context.write('\n')
+            }
+            // Text of links is counted to detect navigation and other link lists
+            "a" => context.output.start_link(),
+            _ => {}
+        }
+
+        // Block elements separate text blocks, while inline elements continue the text of the parent element
+        if element.is_block() {
+            context.output.add_break(&format!("<{}>", element.name));
+        }
+
+        match element.name.as_str() {
+            // Headings and sectioning elements structure the document into sections
+            "h1" | "h2" | "h3" | "h4" | "h5" | "h6" => {
+                context.output.start_heading(element.name[1..].parse().expect("Level of heading is a digit."));
+            }
+            "article" | "section" => context.output.start_section(),
+            _ => {}
+        }
+        // Include content of element if content of parent element is included
+        None
+    }
+
+    fn on_end_element(&mut self, element: &Element, context: &mut HandlerContext) {
+        if element.is_block() || element.name == "title" {
+            context.output.add_break(&format!("</{}>", element.name));
+        }
+
+        match element.name.as_str() {
+            "a" => context.output.end_link(),
+            "title" => context.output.end_title(),
+            "h1" | "h2" | "h3" | "h4" | "h5" | "h6" => context.output.end_heading(),
+            "article" | "section" => context.output.end_section(),
+            _ => {}
+        }
+    }
+}
+
+// Handler including and excluding elements matching the CSS selectors of the request and the profile
+pub struct SelectorHandler {
+    include: Option<SelectorList>,
+    exclude: Option<SelectorList>,
+    // Number of open excluded elements (content of excluded elements is not included by include selectors)
+    excluded_elements: usize,
+}
+
+impl SelectorHandler {
+    // Include selectors of the request replace the ones of the profile, while exclude selectors of both are used
+    pub fn new(parameters: &ExtractParameters, profile: Option<&Profile>) -> Result<Self, Error> {
+        let include = match (&parameters.include, profile) {
+            (Some(include), _) => Some(include.clone()),
+            (None, Some(profile)) if !profile.include.is_empty() => Some(profile.include.join(",")),
+            _ => None,
+        };
+        let exclude: Vec<String> = parameters
+            .exclude
+            .iter()
+            .chain(profile.map(|profile| &profile.exclude).into_iter().flatten())
+            .cloned()
+            .collect();
+
+        Ok(Self {
+            include: include.as_deref().map(SelectorList::parse).transpose()?,
+            exclude: (!exclude.is_empty()).then(|| SelectorList::parse(&exclude.join(","))).transpose()?,
+            excluded_elements: 0,
+        })
+    }
+
+    fn is_excluded(&self, element: &Element, context: &HandlerContext) -> bool {
+        self.exclude.as_ref().is_some_and(|exclude| exclude.matches(element, context.elements))
+    }
+}
+
+impl ElementHandler for SelectorHandler {
+    fn on_start_element(&mut self, element: &Element, context: &mut HandlerContext) -> Option<bool> {
+        // Selectors are evaluated against the open elements (ancestors of the element)
+        if self.is_excluded(element, context) {
+            self.excluded_elements += 1;
+            return Some(false);
+        }
+        match &self.include {
+            Some(include) if self.excluded_elements == 0 && include.matches(element, context.elements) => Some(true),
+            // Only the selected elements are included if there are include selectors
+            Some(_) if element.name == "body" || element.name == "title" => Some(false),
+            _ => None,
+        }
+    }
+
+    fn on_end_element(&mut self, element: &Element, context: &mut HandlerContext) {
+        if self.is_excluded(element, context) {
+            self.excluded_elements -= 1;
+        }
+    }
+}
+
+// Handler excluding content not visible on the page independently from the element name
+pub struct HiddenContentHandler {
+    // Number of open hidden elements (content of hidden elements is not included by other handlers)
+    hidden_elements: usize,
+}
+
+impl ElementHandler for HiddenContentHandler {
+    fn on_start_element(&mut self, element: &Element, context: &mut HandlerContext) -> Option<bool> {
+        if HiddenContentHandler::is_hidden(element, context.parameters) {
+            self.hidden_elements += 1;
+        }
+        (self.hidden_elements > 0).then_some(false)
+    }
+
+    fn on_end_element(&mut self, element: &Element, context: &mut HandlerContext) {
+        if HiddenContentHandler::is_hidden(element, context.parameters) {
+            self.hidden_elements -= 1;
+        }
+    }
+}
+
+impl HiddenContentHandler {
+    // Decide whether the content of an element is hidden on the rendered page
+    fn is_hidden(element: &Element, parameters: &ExtractParameters) -> bool {
+        if parameters.skip_hidden {
+            // Content of templates is never rendered
+            if element.name == "template" || element.has_attribute("hidden") {
+                return true;
+            }
+            if element.style_property("display").is_some_and(|value| value == "none")
+                || element.style_property("visibility").is_some_and(|value| value == "hidden" || value == "collapse")
+            {
+                return true;
+            }
+        }
+
+        if parameters.skip_aria_hidden && element.attribute("aria-hidden").is_some_and(|value| value.eq_ignore_ascii_case("true")) {
+            return true;
+        }
+
+        parameters.skip_noscript && element.name == "noscript"
+    }
+}
+
+// Handler keeping the whitespace of preformatted elements (like code samples and poems) as it is
+pub struct PreformattedHandler {
+    // Whether the next character is the first one after the open tag of a pre, listing or textarea element
+    skip_newline: bool,
+}
+
+impl ElementHandler for PreformattedHandler {
+    fn on_start_element(&mut self, element: &Element, context: &mut HandlerContext) -> Option<bool> {
+        self.skip_newline = false;
+        if PreformattedHandler::is_preformatted(element) {
+            // Text of form fields is separated from the text around them like the text of block elements
+            if element.name == "textarea" {
+                context.output.add_break("<textarea>");
+            }
+            // A new line character immediately after the open tag is not part of the content
+            self.skip_newline = matches!(element.name.as_str(), "pre" | "listing" | "textarea");
+            // Text of pre, listing and plaintext elements is usually code, unlike text of form fields and styled elements
+            context.output.start_preformatted(matches!(element.name.as_str(), "pre" | "listing" | "plaintext"));
+        }
+        None
+    }
+
+    fn on_end_element(&mut self, element: &Element, context: &mut HandlerContext) {
+        self.skip_newline = false;
+        if PreformattedHandler::is_preformatted(element) {
+            context.output.end_preformatted();
+            if element.name == "textarea" {
+                context.output.add_break("</textarea>");
+            }
+        }
+    }
+
+    fn on_text(&mut self, ch: char, _context: &mut HandlerContext) -> bool {
+        std::mem::take(&mut self.skip_newline) && ch == '\n'
+    }
+}
+
+impl PreformattedHandler {
+    fn is_preformatted(element: &Element) -> bool {
+        matches!(element.name.as_str(), "pre" | "listing" | "plaintext" | "textarea")
+            || element.style_property("white-space").is_some_and(|value| value == "pre" || value == "pre-wrap" || value == "break-spaces")
+    }
+}
+
+// Handler annotating the text of inline formatting elements and links with spans in the Advanced output
+pub struct SpanHandler {}
+
+impl SpanHandler {
+    fn kind(element: &Element) -> Option<&'static str> {
+        match element.name.as_str() {
+            "b" | "strong" => Some("strong"),
+            "i" | "em" => Some("em"),
+            "a" => Some("a"),
+            "code" | "kbd" | "samp" | "tt" => Some("code"),
+            "mark" => Some("mark"),
+            "sup" => Some("sup"),
+            "sub" => Some("sub"),
+            _ => None,
+        }
+    }
+}
+
+impl ElementHandler for SpanHandler {
+    fn on_start_element(&mut self, element: &Element, context: &mut HandlerContext) -> Option<bool> {
+        if let Some(kind) = SpanHandler::kind(element) {
+            context.output.start_span(kind, element.attribute("href").filter(|_| kind == "a"));
+        }
+        None
+    }
+
+    fn on_end_element(&mut self, element: &Element, context: &mut HandlerContext) {
+        if SpanHandler::kind(element).is_some() {
+            context.output.end_span();
+        }
+    }
+}
+
+// Handler tracking the languages declared by the lang attributes of elements
+pub struct LanguageHandler {}
+
+impl LanguageHandler {
+    fn language(element: &Element) -> Option<&str> {
+        element.attribute("lang").or(element.attribute("xml:lang")).map(|language| language.trim())
+    }
+}
+
+impl ElementHandler for LanguageHandler {
+    fn on_start_element(&mut self, element: &Element, context: &mut HandlerContext) -> Option<bool> {
+        if let Some(language) = LanguageHandler::language(element) {
+            if element.name == "html" {
+                context.output.set_document_language(language);
+            }
+            context.output.start_language(language);
+        }
+        None
+    }
+
+    fn on_end_element(&mut self, element: &Element, context: &mut HandlerContext) {
+        if LanguageHandler::language(element).is_some() {
+            context.output.end_language();
+        }
+    }
+}
@@ -0,0 +1,148 @@
+// An element of the HTML document with its name and the attributes of its open tag
+pub struct Element {
+    pub name: String,
+    pub attributes: Vec<(String, String)>,
+}
+
+impl Element {
+    // Create an element from its name and the source of its open tag after the name (like ' id="main" hidden/>')
+    pub fn new(name: String, source: &str) -> Self {
+        Self {
+            name,
+            attributes: Element::parse_attributes(source),
+        }
+    }
+
+    // Elements without content and close tag
+    pub fn is_void(&self) -> bool {
+        matches!(
+            self.name.as_str(),
+            "area" | "base" | "br" | "col" | "embed" | "hr" | "img" | "input" | "link" | "meta" | "param" | "source" | "track" | "wbr"
+        )
+    }
+
+    // Elements containing text only, where tags are part of the text
+    pub fn is_raw_text(&self) -> bool {
+        matches!(self.name.as_str(), "textarea" | "plaintext")
+    }
+
+    // Elements displayed as separate blocks on the page (content of inline elements continues the text of the parent
+    // element). Cells of tables are not blocks, they are separated by new line characters in the text of their rows.
+    pub fn is_block(&self) -> bool {
+        if let Some(display) = self.style_property("display") {
+            match display.as_str() {
+                "block" | "flex" | "grid" | "flow-root" | "list-item" | "table" => return true,
+                "inline" | "inline-block" | "inline-flex" | "inline-grid" | "contents" => return false,
+                _ => {}
+            }
+        }
+        matches!(
+            self.name.as_str(),
+            "address" | "article" | "aside" | "blockquote" | "caption" | "center" | "dd" | "details" | "dialog" | "dir" | "div"
+                | "dl" | "dt" | "fieldset" | "figcaption" | "figure" | "footer" | "form" | "h1" | "h2" | "h3" | "h4" | "h5" | "h6"
+                | "header" | "hgroup" | "hr" | "legend" | "li" | "listing" | "main" | "menu" | "nav" | "ol" | "p" | "plaintext"
+                | "pre" | "search" | "section" | "summary" | "table" | "tbody" | "tfoot" | "thead" | "tr" | "ul" | "xmp"
+        )
+    }
+
+    // Name of the element followed by its id and classes (like 'div#main.content')
+    pub fn path_segment(&self) -> String {
+        let mut segment = self.name.clone();
+        if let Some(id) = self.attribute("id").filter(|id| !id.is_empty()) {
+            segment.push('#');
+            segment.push_str(id);
+        }
+        for class in self.classes() {
+            segment.push('.');
+            segment.push_str(class);
+        }
+        segment
+    }
+
+    // Value of an attribute (names of attributes are case insensitive and stored in lowercase)
+    pub fn attribute(&self, name: &str) -> Option<&str> {
+        self.attributes
+            .iter()
+            .find(|(attribute_name, _)| attribute_name == name)
+            .map(|(_, value)| value.as_str())
+    }
+
+    pub fn has_attribute(&self, name: &str) -> bool {
+        self.attribute(name).is_some()
+    }
+
+    pub fn classes(&self) -> impl Iterator<Item = &str> {
+        self.attribute("class").unwrap_or("").split_whitespace()
+    }
+
+    // Value of a property in the inline 'style' attribute (like 'display' in style="display: none")
+    pub fn style_property(&self, name: &str) -> Option<String> {
+        self.attribute("style")?
+            .split(';')
+            .filter_map(|declaration| declaration.split_once(':'))
+            .filter(|(property, _)| property.trim().eq_ignore_ascii_case(name))
+            .map(|(_, value)| value.replace("!important", "").trim().to_lowercase())
+            .next_back()
+    }
+
+    // Parse attributes in any of the name="value", name='value', name=value or name forms
+    fn parse_attributes(source: &str) -> Vec<(String, String)> {
+        let mut attributes = Vec::new();
+        let mut chars = source.chars().peekable();
+
+        loop {
+            // Skip whitespaces and slashes between attributes
+            while chars.next_if(|ch| ch.is_whitespace() || *ch == '/' || *ch == '>').is_some() {}
+
+            let mut name = String::new();
+            while let Some(ch) = chars.next_if(|ch| !ch.is_whitespace() && !matches!(ch, '=' | '>' | '/')) {
+                name.push(ch.to_ascii_lowercase());
+            }
+            if name.is_empty() {
+                return attributes;
+            }
+
+            while chars.next_if(|ch| ch.is_whitespace()).is_some() {}
+
+            let mut value = String::new();
+            if chars.next_if_eq(&'=').is_some() {
+                while chars.next_if(|ch| ch.is_whitespace()).is_some() {}
+
+                match chars.next_if(|ch| *ch == '"' || *ch == '\'') {
+                    // Quoted value lasts until the closing quote
+                    Some(quote) => {
+                        for ch in chars.by_ref() {
+                            if ch == quote {
+                                break;
+                            }
+                            value.push(ch);
+                        }
+                    }
+                    // Unquoted value lasts until the next whitespace
+                    None => {
+                        while let Some(ch) = chars.next_if(|ch| !ch.is_whitespace() && *ch != '>') {
+                            value.push(ch);
+                        }
+                    }
+                }
+            }
+
+            attributes.push((name, Element::decode_entities(&value)));
+        }
+    }
+
+    // Replace the most common entities in attribute values
+    fn decode_entities(value: &str) -> String {
+        if !value.contains('&') {
+            return String::from(value);
+        }
+        value
+            .replace("&quot;", "\"")
+            .replace("&#39;", "'")
+            .replace("&apos;", "'")
+            .replace("&lt;", "<")
+            .replace("&gt;", ">")
+            .replace("&nbsp;", " ")
+            .replace("&amp;", "&")
+    }
+}
@@ -0,0 +1,97 @@
+use super::element::Element;
+use crate::{text_extractor::{ContentMode, ExtractParameters}, util::{json_writer::JsonWriter, main_content::MainContent}};
+
+// Element handlers decide from which elements to include contents in the output and when text blocks are separated.
+// Handlers are called in the order of their registration, so a handler can override the decisions of the earlier ones.
+pub trait ElementHandler: Send {
+    // Method is called when the open tag of an element is found. The returned value enables or disables the output for
+    // the content of the element. (None keeps the setting of the parent element.)
+    fn on_start_element(&mut self, _element: &Element, _context: &mut HandlerContext) -> Option<bool> {
+        None
+    }
+
+    // Method is called when an element is closed (by its own close tag, by the close tag of a parent element or
+    // immediately after the open tag for empty elements)
+    fn on_end_element(&mut self, _element: &Element, _context: &mut HandlerContext) {}
+
+    // Method is called for each character of the text content. Returning true means that the character was
+    // handled, therefore it is not written to the output and not passed to the remaining handlers.
+    fn on_text(&mut self, _ch: char, _context: &mut HandlerContext) -> bool {
+        false
+    }
+
+    // Method is called at the end of the document (elements may still be open if the document is incomplete)
+    fn on_end_document(&mut self, _context: &mut HandlerContext) {}
+}
+
+// State of the parser accessible by the element handlers
+pub struct HandlerContext<'a> {
+    // Elements opened but not closed yet (the parent of the current element is the last one)
+    pub elements: &'a [Element],
+    pub output: &'a mut JsonWriter,
+    // Whether the content of the current element is written to the output
+    pub output_enabled: bool,
+    pub parameters: &'a ExtractParameters,
+}
+
+impl HandlerContext<'_> {
+    // Write a character to the output if output is enabled
+    pub fn write(&mut self, ch: char) {
+        if self.output_enabled {
+            let is_fragment_start = self.output.is_fragment_start(ch);
+            self.output.write_char(ch);
+
+            // Context of a fragment is taken from the elements containing its first character (the main content is
+            // detected for the summary as well)
+            if is_fragment_start && (self.parameters.content_mode == ContentMode::Main || self.parameters.summary > 0) {
+                self.output.fragment_mut().hint = self.fragment_hint();
+            }
+            if is_fragment_start && self.parameters.paths {
+                self.output.fragment_mut().path = Some(self.fragment_path());
+            }
+        }
+    }
+
+    // Path of the innermost block element containing the fragment (inline elements inside it are not included)
+    fn fragment_path(&self) -> String {
+        let length = self
+            .elements
+            .iter()
+            .rposition(|element| element.is_block() || element.name == "html" || element.name == "body")
+            .map_or(self.elements.len(), |position| position + 1);
+        let segments: Vec<String> = self.elements[..length].iter().map(|element| element.path_segment()).collect();
+        segments.join(" > ")
+    }
+
+    // Score of the open elements used to detect the main content. Names of the root elements are not taken
+    // into account, because they contain the whole page.
+    fn fragment_hint(&self) -> i32 {
+        let (mut positive, mut negative) = (0, 0);
+        for element in self.elements.iter().filter(|element| element.name != "html" && element.name != "body") {
+            let names = element.attribute("id").into_iter().chain(element.attribute("class"));
+            let hint = MainContent::element_hint(&element.name, names);
+            positive = hint.max(positive);
+            negative += hint.min(0);
+        }
+        (positive + negative).max(-50)
+    }
+}
+
+// Handlers registered on an extractor are borrowed for the time of an extraction
+impl<T: ElementHandler + ?Sized> ElementHandler for &mut T {
+    fn on_start_element(&mut self, element: &Element, context: &mut HandlerContext) -> Option<bool> {
+        (**self).on_start_element(element, context)
+    }
+
+    fn on_end_element(&mut self, element: &Element, context: &mut HandlerContext) {
+        (**self).on_end_element(element, context)
+    }
+
+    fn on_text(&mut self, ch: char, context: &mut HandlerContext) -> bool {
+        (**self).on_text(ch, context)
+    }
+
+    fn on_end_document(&mut self, context: &mut HandlerContext) {
+        (**self).on_end_document(context)
+    }
+}
@@ -1,4 +1,4 @@
-use super::{content_parser::ContentParser, parser_context::ParserContext, processor::Processor};
+use super::{content_parser::ContentParser, element::Element, parser_context::ParserContext, raw_text_parser::RawTextParser};
 use crate::error::Error;
 use tokio::io::{AsyncRead, AsyncWrite};
 
@@ -13,10 +13,14 @@ impl ElementParser {
     ) -> Result<Option<String>, Error> {
         let mut name_length = 0;
 
+        // Quote character of the attribute value being read ('>' character doesn't close the tag inside quotes)
+        let mut quote: Option<char> = None;
+        let mut after_equal_sign = false;
+
         loop {
             match context.input.read_char().await? {
                 // If the '>' character was received than the and of the tag was reached
-                '>' => {
+                '>' if quote.is_none() => {
                     context.buffer.push('>');
 
                     // Detect the length of element name (without attributes)
@@ -28,30 +32,48 @@ impl ElementParser {
                     if !context.buffer.starts_with("</") && !context.buffer.ends_with("/>") {
                         let element_name =
                             String::from(&context.buffer[1..name_length - 1]).to_lowercase();
-                         
+
                         if element_name == "!doctype" {
                             return Ok(None);
                         }
 
+                        let element = Element::new(element_name, &context.buffer[name_length - 1..]);
+                        context.buffer.clear();
+
+                        // Void elements like <br> have no content and close tag
+                        if element.is_void() {
+                            ElementParser::parse_empty_element(context, element).await?;
+                            return Ok(None);
+                        }
+
                         let old_output_enabled = context.output_enabled;
 
-                        // Processor decided whether to extract content from the element found
+                        // Element handlers decide whether to extract content from the element found
                         if let Some(new_output_enabled) =
-                            Processor::on_start_element(&element_name, context).await?
+                            context.on_start_element(&element).await?
                         {
                             context.output_enabled = new_output_enabled;
                         }
 
-                        context.buffer.clear();
-
                         // Content is used to process element content, what returns only if a close tag was found
-                        let close_element = ContentParser::parse(context, false).await?;
+                        // (content of raw text elements ends at their own close tag)
+                        let raw_text_name = element.is_raw_text().then(|| element.name.clone());
+                        context.elements.push(element);
+                        let close_element = match raw_text_name {
+                            Some(name) => {
+                                RawTextParser::parse(context, &name).await?;
+                                Some(name)
+                            }
+                            None => ContentParser::parse(context, false).await?,
+                        };
+                        let element = context.elements.pop().expect("Element was pushed before parsing its content.");
+
+                        // The close tag may not match this open tag. (The close tag of a parent element closes this element as well.)
+                        context.on_end_element(&element).await?;
+                        context.output_enabled = old_output_enabled;
 
-                        // The close tag may not match this open tag. (Some tags like <br> has no closing pair.)
                         if let Some(val) = close_element {
-                            // If the close tag matches the open tag, then change back the setting whether to extract content
-                            if val == element_name {
-                                context.output_enabled = old_output_enabled;
+                            if val == element.name {
                                 // None means that open tag for the closing one was already found
                                 return Ok(None);
                             } else {
@@ -62,7 +84,7 @@ impl ElementParser {
                     }
                     // If there is a slash at the end of the name, then it is an XHTML compatible empty element like <br/>
                     else if context.buffer.ends_with("/>") {
-                        
+
                         let mut element_name =
                             String::from(&context.buffer[1..name_length - 1]).to_lowercase();
                         let trimmed = element_name.strip_suffix('/');
@@ -70,21 +92,19 @@ impl ElementParser {
                             element_name = trimmed_value.into();
                         }
 
-                        // Processor is called in the same way as for an open and close tag (<br/> is the same as <br></br>)
-                        Processor::on_start_element(&element_name, context).await?;
-                        Processor::on_end_element(&element_name, context).await?;
-
+                        let element = Element::new(element_name, &context.buffer[name_length - 1..]);
                         context.buffer.clear();
 
+                        ElementParser::parse_empty_element(context, element).await?;
+
                         // No element name is returned, because there is no close tag to find open tag for
                         return Ok(None);
                     }
                     // In all remaining cases it must be a close tag
                     else {
                         let element_name =
-                            String::from(&context.buffer[2..name_length - 1]).to_lowercase();                        
-                        
-                        Processor::on_end_element(&element_name, context).await?;
+                            String::from(&context.buffer[2..name_length - 1]).to_lowercase();
+
                         context.buffer.clear();
 
                         // Return element name to find the matching open tag
@@ -95,13 +115,39 @@ impl ElementParser {
                 ch => {
                     // Tag content is stored in the buffer, because it contains valuable information like element name and attributes
                     context.buffer.push(ch);
-                    
+
                     // Check for whitespaces to find the end of the element name
                     if ch.is_whitespace() && name_length == 0 {
                         name_length = context.buffer.len();
                     }
+
+                    // Keep track of quoted attribute values
+                    if let Some(quote_char) = quote {
+                        if ch == quote_char {
+                            quote = None;
+                        }
+                    } else if (ch == '"' || ch == '\'') && after_equal_sign {
+                        quote = Some(ch);
+                    }
+                    if !ch.is_whitespace() {
+                        after_equal_sign = ch == '=';
+                    }
                 }
             }
         }
     }
+
+    // Element handlers are called in the same way for an empty element as for an open and close tag (<br/> is the same as <br></br>)
+    async fn parse_empty_element<R: AsyncRead + Unpin + Send, W: AsyncWrite + Unpin + Send>(
+        context: &mut ParserContext<'_, R, W>,
+        element: Element
+    ) -> Result<(), Error> {
+        let old_output_enabled = context.output_enabled;
+        if let Some(new_output_enabled) = context.on_start_element(&element).await? {
+            context.output_enabled = new_output_enabled;
+        }
+        context.on_end_element(&element).await?;
+        context.output_enabled = old_output_enabled;
+        Ok(())
+    }
 }
@@ -1,37 +1,104 @@
-use super::{content_parser::ContentParser, parser_context::ParserContext};
+use super::{content_parser::ContentParser, default_handlers::default_handlers, element_handler::ElementHandler, parser_context::ParserContext, profile::Profile};
 use crate::{
-    error::Error, text_extractor::{OutputFormat, TextExtractor}, util::{json_writer::JsonWriter, utf8_reader::Utf8Reader, utf8_writer::Utf8Writer}
+    error::Error, text_extractor::{ExtractParameters, TextExtractor}, util::{fingerprint::Fingerprint, json_writer::JsonWriter, utf8_reader::Utf8Reader, utf8_writer::Utf8Writer}
 };
 use tokio::io::{AsyncRead, AsyncWrite};
 
 // Implementation of the TextExtractor trait for the 'html' file format
-pub struct HtmlTextExtractor {}
+#[derive(Default)]
+pub struct HtmlTextExtractor {
+    // Element handlers registered in addition to the default ones
+    handlers: Vec<Box<dyn ElementHandler>>,
+    // Extraction rules customizing the default handlers
+    profile: Option<Profile>
+}
 
-impl TextExtractor for HtmlTextExtractor {
-    async fn extract<R: AsyncRead + Unpin + Send, W: AsyncWrite + Unpin + Send>(
+impl HtmlTextExtractor {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn with_profile(profile: Option<Profile>) -> Self {
+        Self { handlers: Vec::new(), profile }
+    }
+
+    // Register an element handler. Handlers are called after the default ones, so they can override their decisions
+    pub fn register_handler(&mut self, handler: Box<dyn ElementHandler>) {
+        self.handlers.push(handler);
+    }
+
+    // Compute the fingerprint of the text of a document (it is taken from the output instead of the serialized JSON)
+    pub async fn fingerprint<R: AsyncRead + Unpin + Send>(&mut self, reader: &mut R, parameters: &ExtractParameters) -> Result<Fingerprint, Error> {
+        let parameters = ExtractParameters { fingerprints: true, ..parameters.clone() };
+        let (mut output, result) = self.parse(reader, &mut tokio::io::sink(), &parameters, false).await?;
+        result?;
+        Ok(output.fingerprint().expect("Fingerprint is computed when it is requested."))
+    }
+
+    // Extract the text of a document with its text blocks separated by new line characters (errors of the document are
+    // returned instead of being written to the output)
+    pub async fn text<R: AsyncRead + Unpin + Send>(&mut self, reader: &mut R, parameters: &ExtractParameters) -> Result<String, Error> {
+        let (mut output, result) = self.parse(reader, &mut tokio::io::sink(), parameters, true).await?;
+        result?;
+        Ok(output.take_text())
+    }
+
+    // Parse the document and return the output after the end of the document was written with the result of parsing
+    // (errors of reading and writing the streams are returned as errors)
+    async fn parse<R: AsyncRead + Unpin + Send, W: AsyncWrite + Unpin + Send>(
         &mut self,
         reader: &mut R,
         writer: &mut W,
-        output_format: OutputFormat
-    ) -> Result<(), Error> {
+        parameters: &ExtractParameters,
+        collect_text: bool
+    ) -> Result<(JsonWriter, Result<(), Error>), Error> {
         
+        // Invalid parameters (like selectors) are reported in the output in the same way as parsing errors
+        let (mut handlers, parameter_error) = match default_handlers(parameters, self.profile.as_ref()) {
+            Ok(handlers) => (handlers, None),
+            Err(error) => (Vec::new(), Some(error))
+        };
+        for handler in self.handlers.iter_mut() {
+            handlers.push(Box::new(handler.as_mut()));
+        }
+
         // Create the parser context
         let mut utf8_reader = Utf8Reader::new(reader);
         let utf8_writer = Utf8Writer::new(writer, 1024);
-        let mut text_writer = JsonWriter::new(utf8_writer, output_format);
         let buffer = String::new();        
         
-        let mut context = ParserContext::new(&mut utf8_reader, &mut text_writer, buffer);
+        let mut context = ParserContext::new(&mut utf8_reader, utf8_writer, buffer, handlers, parameters);
+        if collect_text {
+            context.output.collect_text();
+        }
 
         context.start().await?;
 
         // Use a state machine to parse the HTML file. The start state is 'content'
-        match ContentParser::parse(&mut context, true).await
+        let result = match parameter_error {
+            Some(error) => Err(error),
+            None => ContentParser::parse(&mut context, true).await.map(|_| ())
+        };
+
+        match &result
         {
             Ok(_) => context.end("").await?,
             Err(error) => context.end(&error.message).await?
         }
 
+        Ok((context.output, result))
+    }
+}
+
+impl TextExtractor for HtmlTextExtractor {
+    async fn extract<R: AsyncRead + Unpin + Send, W: AsyncWrite + Unpin + Send>(
+        &mut self,
+        reader: &mut R,
+        writer: &mut W,
+        parameters: &ExtractParameters
+    ) -> Result<(), Error> {
+        // Errors of the document are written to the output
+        let _ = self.parse(reader, writer, parameters, false).await?;
         Ok(())
     }
 }
@@ -39,18 +106,29 @@ impl TextExtractor for HtmlTextExtractor {
 #[cfg(test)]
 mod tests {
 
-    use crate::{body_text_extractor::BodyTextExtractor, text_extractor::OutputFormat};
+    use super::HtmlTextExtractor;
+    use crate::{
+        body_text_extractor::BodyTextExtractor,
+        html::{element::Element, element_handler::{ElementHandler, HandlerContext}, profile::Profile},
+        util::fingerprint::Fingerprint,
+        text_extractor::{ChunkUnit, ContentMode, ExtractParameters, Granularity, InputFormat, Normalization, OutputFormat, TableFormat, TextExtractor}
+    };
     use axum::body::{to_bytes, Body};
     use serde::Deserialize;
 
     #[derive(Deserialize)]
     struct ExtractedText {
-        results: Vec<ExtractedTextFragment>
+        results: Vec<ExtractedTextFragment>,
+        error: Option<String>
     }
 
     #[derive(Deserialize)]
     struct ExtractedTextFragment {
-        text: String
+        separator: Option<String>,
+        text: String,
+        score: Option<f64>,
+        #[serde(default)]
+        code: bool
     }
 
     #[tokio::test]
@@ -103,14 +181,484 @@ mod tests {
         do_test("<html><body><script>if (document.body.addEventListener(\"load\", (t => { t.target.classList.contains(\"interactive\") && t.target.setAttribute(\"data-readystate\", \"complete\") }), { capture: !0 }), window && document.documentElement) { const t = { light: \"#ffffff\", dark: \"#1b1b1b\" }; try { const e = window.localStorage.getItem(\"theme\"); e && (document.documentElement.className = e, document.documentElement.style.backgroundColor = t[e]) } catch (t) { console.warn(\"Unable to read theme from localStorage\", t) } }</script><div id=\"root\">Text</div></body></html>", "\nText").await;
     }
 
+    #[tokio::test]
+    async fn test_blocks() {
+        do_test("<html><body><h2>Title</h2><span>Text</span></body></html>", "\nTitle\nText").await;
+        do_test("<html><body><b>Bo</b>ld <i>text</i><span> with </span><em>inline</em>elements</body></html>", "Bold text with inlineelements").await;
+        do_test("<html><body><section>Section<blockquote>Quote</blockquote>text</section></body></html>", "\nSection\nQuote\ntext").await;
+        do_test("<html><body><header>Header</header><figure><img src=\"a.png\"><figcaption>Caption</figcaption></figure></body></html>", "\nHeader\nCaption").await;
+        do_test("<html><body><table><tr><td>A</td><td>B</td></tr><tr><td>C</td></tr></table></body></html>", "\nA\nB\nC").await;
+        do_test("<html><body><span style=\"display: block\">Block</span><div style=\"display:inline\">Inline</div></body></html>", "\nBlock\nInline").await;
+
+        let parameters = ExtractParameters { output_format: OutputFormat::Advanced, ..Default::default() };
+        let fragments = extract("<html><body><div>Outer<div>Inner</div>Tail</div></body></html>", &parameters).await.results;
+        let texts: Vec<(Option<&str>, &str)> = fragments.iter().map(|fragment| (fragment.separator.as_deref(), fragment.text.as_str())).collect();
+        assert_eq!(texts, vec![(None, ""), (Some("<div>"), "Outer"), (Some("<div>"), "Inner"), (Some("</div>"), "Tail")]);
+    }
+
+    #[tokio::test]
+    async fn test_spans() {
+        let input = "<html><body><p>Some <b>bold</b> and <i>it<em>alic</em></i> text with a <a href=\"/page\">link</a>.</p>\
+            <p><strong>Strong <p>paragraph</strong> x<sup>2</sup> <code>main()</code></p>\
+            <ol reversed><li><mark>Marked</mark></li></ol></body></html>";
+        let parameters = ExtractParameters { output_format: OutputFormat::Advanced, list_markers: true, ..Default::default() };
+        let output = extract_json(input, &parameters).await;
+
+        assert_eq!(output["results"][1]["text"], "Some bold and italic text with a link.");
+        assert_eq!(output["results"][1]["spans"], serde_json::json!([
+            { "kind": "strong", "start": 5, "end": 9 },
+            { "kind": "em", "start": 14, "end": 20 },
+            { "kind": "em", "start": 16, "end": 20 },
+            { "kind": "a", "start": 33, "end": 37, "href": "/page" }
+        ]));
+        assert_eq!(output["results"][2]["spans"], serde_json::json!([{ "kind": "strong", "start": 0, "end": 6 }]));
+        assert_eq!(output["results"][3]["spans"], serde_json::json!([{ "kind": "strong", "start": 0, "end": 9 }]));
+        assert_eq!(output["results"][4]["text"], "x2 main()");
+        assert_eq!(output["results"][4]["spans"], serde_json::json!([
+            { "kind": "sup", "start": 1, "end": 2 },
+            { "kind": "code", "start": 3, "end": 9 }
+        ]));
+        assert_eq!(output["results"][5]["text"], "1. Marked");
+        assert_eq!(output["results"][5]["spans"], serde_json::json!([{ "kind": "mark", "start": 3, "end": 9 }]));
+    }
+
+    #[tokio::test]
+    async fn test_paths() {
+        let input = "<html><body>Intro<main id=\"content\"><article class=\"post featured\"><h1>Title</h1>\
+            <p>First <b>paragraph</b></p><div><b>Second</b> paragraph</div><h2>Chapter</h2><p>Third</p></article></main></body></html>";
+        let parameters = ExtractParameters { output_format: OutputFormat::Advanced, paths: true, ..Default::default() };
+        let output = extract_json(input, &parameters).await;
+
+        let blocks: Vec<(&str, &str, Option<&str>)> = output["results"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .map(|result| (result["text"].as_str().unwrap(), result["path"].as_str().unwrap(), result["heading"].as_str()))
+            .collect();
+        assert_eq!(blocks, vec![
+            ("Intro", "html > body", None),
+            ("Title", "html > body > main#content > article.post.featured > h1", None),
+            ("First paragraph", "html > body > main#content > article.post.featured > p", Some("Title")),
+            ("Second paragraph", "html > body > main#content > article.post.featured > div", Some("Title")),
+            ("Chapter", "html > body > main#content > article.post.featured > h2", Some("Title")),
+            ("Third", "html > body > main#content > article.post.featured > p", Some("Chapter"))
+        ]);
+    }
+
+    #[tokio::test]
+    async fn test_language() {
+        let input = "<html lang=\"en\"><body><p>The weather is going to be warm and sunny for the rest of the week.</p>\
+            <p lang=\"de\">Das Wetter wird für den Rest der Woche warm und sonnig sein.</p>\
+            <p>Le temps sera chaud et ensoleillé pour le reste de la semaine.</p><p>Hi</p></body></html>";
+        let parameters = ExtractParameters { output_format: OutputFormat::Advanced, detect_language: true, ..Default::default() };
+        let output = extract_json(input, &parameters).await;
+
+        let languages: Vec<(&str, Option<&str>)> = output["results"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .skip(1)
+            .map(|result| (result["language"]["declared"].as_str().unwrap(), result["language"]["detected"].as_str()))
+            .collect();
+        assert_eq!(languages, vec![("en", Some("en")), ("de", Some("de")), ("en", Some("fr")), ("en", None)]);
+        assert_eq!(output["language"]["declared"], "en");
+        assert_eq!(output["language"]["detected"], "en");
+
+        let parameters = ExtractParameters { detect_language: true, content_language: Some(String::from("hu")), ..Default::default() };
+        let output = extract_json("<html><body>A hét további részében meleg és napos idő várható az egész országban.</body></html>", &parameters).await;
+        assert_eq!(output["language"]["declared"], "hu");
+        assert_eq!(output["language"]["detected"], "hu");
+    }
+
+    #[tokio::test]
+    async fn test_sentences() {
+        let input = "<html><body><p>Dr. Smith met J. Doe. They <b>talked</b>.</p><ol><li>Item</li></ol></body></html>";
+        let parameters = ExtractParameters { output_format: OutputFormat::Advanced, granularity: Granularity::Sentence, list_markers: true, ..Default::default() };
+        let output = extract_json(input, &parameters).await;
+
+        let sentences: Vec<(&str, u64, u64, u64)> = output["results"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .map(|result| (result["text"].as_str().unwrap(), result["block"].as_u64().unwrap(), result["start"].as_u64().unwrap(), result["end"].as_u64().unwrap()))
+            .collect();
+        assert_eq!(sentences, vec![("Dr. Smith met J. Doe.", 0, 0, 21), ("They talked.", 0, 22, 34), ("1. Item", 1, 0, 7)]);
+        assert_eq!(output["results"][1]["spans"], serde_json::json!([{ "kind": "strong", "start": 27, "end": 33 }]));
+    }
+
+    #[tokio::test]
+    async fn test_stats() {
+        let input = "<html lang=\"en\"><body><p>The cat sat on the mat. It was <a href=\"/cat\">happy</a>.</p><p lang=\"de\">Das ist gut.</p></body></html>";
+        let parameters = ExtractParameters { output_format: OutputFormat::Advanced, stats: true, ..Default::default() };
+        let output = extract_json(input, &parameters).await;
+
+        // Links and declared languages of the elements are counted in the statistics of the text blocks
+        assert_eq!(output["results"][1]["stats"]["link_ratio"], 0.14);
+        assert_eq!(output["results"][1]["stats"]["flesch_reading_ease"], 108.27);
+        assert_eq!(output["results"][2]["stats"]["readability"]["formula"], "Amstad");
+        assert_eq!(output["stats"]["words"], 12);
+        assert_eq!(output["stats"]["paragraphs"], 2);
+
+        // Redacted texts are counted after the redaction
+        let input = "<html><body><p>Write to john@example.com now.</p></body></html>";
+        let parameters = ExtractParameters { output_format: OutputFormat::Advanced, stats: true, redact: true, ..Default::default() };
+        let output = extract_json(input, &parameters).await;
+        assert_eq!(output["results"][1]["text"], "Write to [EMAIL] now.");
+        assert_eq!(output["results"][1]["stats"]["characters"], 21);
+        assert_eq!(output["results"][1]["stats"]["words"], 4);
+    }
+
+    #[tokio::test]
+    async fn test_document_analyses() {
+        let input = "<html><head><title>Solar panels</title></head><body><nav class=\"menu\"><p>Solar panels shop. Buy solar panels online.</p></nav>\
+            <article><h1>Installing solar panels</h1><p>Solar panels convert sunlight into electricity for homes. The weather was nice \
+            yesterday. Modern solar panels produce more electricity than older panels.</p><p>Batteries store the electricity of solar \
+            panels. Call +36 30 123 4567 before 2024-03-05.</p></article></body></html>";
+        let parameters = ExtractParameters { keywords: 1, summary: 2, entities: true, fingerprints: true, ..Default::default() };
+        let output = extract_json(input, &parameters).await;
+
+        // Keywords are weighted by the title and the headings, and the summary is selected from the main content
+        assert_eq!(output["keywords"][0]["text"], "Solar panels");
+        let summary: Vec<&str> = output["summary"].as_array().unwrap().iter().map(|sentence| sentence["text"].as_str().unwrap()).collect();
+        assert_eq!(summary, vec!["Solar panels convert sunlight into electricity for homes.", "Batteries store the electricity of solar panels."]);
+        let text = output["results"][0]["text"].as_str().unwrap();
+        for sentence in output["summary"].as_array().unwrap().iter().chain(output["entities"].as_array().unwrap()) {
+            let (start, end) = (sentence["start"].as_u64().unwrap() as usize, sentence["end"].as_u64().unwrap() as usize);
+            assert_eq!(text.chars().skip(start).take(end - start).collect::<String>(), sentence["text"].as_str().unwrap());
+        }
+        let entities: Vec<&str> = output["entities"].as_array().unwrap().iter().map(|entity| entity["type"].as_str().unwrap()).collect();
+        assert_eq!(entities, vec!["phone", "date"]);
+
+        // Fingerprints can be computed without serializing the output
+        let fingerprint = serde_json::from_value::<Fingerprint>(output["fingerprint"].clone()).unwrap();
+        let computed = HtmlTextExtractor::new().fingerprint(&mut input.as_bytes(), &ExtractParameters::default()).await.unwrap();
+        assert_eq!(fingerprint.compare(&computed).unwrap().simhash_distance, 0);
+        let text = HtmlTextExtractor::new().text(&mut input.as_bytes(), &ExtractParameters::default()).await.unwrap();
+        assert!(text.starts_with("Solar panels\nSolar panels shop."));
+    }
+
+    #[tokio::test]
+    async fn test_redaction() {
+        let input = "<html><body><p>Write to <b>jane.doe@example.com</b> or <a href=\"mailto:jane.doe@example.com\">mail</a> or \
+            <a href=\"/profile?email=jane.doe@example.com\">visit</a>.</p></body></html>";
+        let parameters = ExtractParameters { output_format: OutputFormat::Advanced, redact: true, entities: true, ..Default::default() };
+        let output = extract_json(input, &parameters).await;
+
+        assert_eq!(output["results"][1]["text"], "Write to [EMAIL] or mail or visit.");
+        assert_eq!(output["results"][1]["spans"], serde_json::json!([
+            { "kind": "strong", "start": 9, "end": 16 },
+            { "kind": "a", "start": 20, "end": 24 },
+            { "kind": "a", "start": 28, "end": 33, "href": "/profile?email=[EMAIL]" }
+        ]));
+        assert_eq!(output["redactions"]["email"], 3);
+        // Entities are found in the redacted text
+        assert_eq!(output["entities"], serde_json::json!([]));
+    }
+
+    #[tokio::test]
+    async fn test_normalization() {
+        let input = "<html><body>Cafe&#769; <b>nai&#776;ve</b> co&shy;op&zwj;eration &ldquo;quoted&rdquo; &ndash; \u{FB01}ne&#8239;print</body></html>";
+        do_test(input, "Cafe\u{301} nai\u{308}ve co\u{AD}op\u{200D}eration “quoted” – \u{FB01}ne\u{202F}print").await;
+
+        let parameters = ExtractParameters {
+            output_format: OutputFormat::Advanced,
+            normalization: Normalization::Nfc,
+            remove_invisible: true,
+            fold_punctuation: true,
+            expand_ligatures: true,
+            ..Default::default()
+        };
+        let output = extract_json(input, &parameters).await;
+        assert_eq!(output["results"][0]["text"], "Café naïve cooperation \"quoted\" - fine print");
+        assert_eq!(output["results"][0]["spans"], serde_json::json!([{ "kind": "strong", "start": 5, "end": 10 }]));
+    }
+
+    #[tokio::test]
+    async fn test_input_format() {
+        let extract_body = |body: Body, parameters: ExtractParameters| async move {
+            let response_body = BodyTextExtractor::extract(body, parameters, None).await;
+            let response_bytes = to_bytes(response_body, usize::MAX).await.unwrap();
+            serde_json::from_slice::<serde_json::Value>(&response_bytes).unwrap()
+        };
+
+        // Formats are sniffed from the beginning of the body
+        let feed = "<?xml version=\"1.0\"?><rss version=\"2.0\"><channel><item><title>News</title></item></channel></rss>";
+        let output = extract_body(Body::from(feed), ExtractParameters::default()).await;
+        assert_eq!(output["input_format"], "feed");
+        assert_eq!(output["results"][0]["title"], "News");
+
+        let output = extract_body(Body::from(&b"%PDF-1.7\n%\xe2\xe3\xcf\xd3"[..]), ExtractParameters::default()).await;
+        assert_eq!(output["error"], "Unsupported input format: pdf");
+
+        let parameters = ExtractParameters {
+            input_format: Some(InputFormat::Html),
+            content_type: Some(String::from("application/xml")),
+            ..Default::default()
+        };
+        let output = extract_body(Body::from("<html><body><p>Data</p></body></html>"), parameters).await;
+        assert_eq!(output["input_format"], "html");
+    }
+
+    #[tokio::test]
+    async fn test_hidden_content() {
+        do_test("<html><body>Visible<div hidden>Hidden</div></body></html>", "Visible").await;
+        do_test("<html><body>Visible<span aria-hidden=\"true\">Hidden</span> Text</body></html>", "Visible Text").await;
+        do_test("<html><body>Visible<span aria-hidden=\"false\"> Text</span></body></html>", "Visible Text").await;
+        do_test("<html><body>Visible<div style=\"color: red; display:none\">Hidden</div></body></html>", "Visible").await;
+        do_test("<html><body>Visible<p style='visibility: hidden !important'>Hidden</p></body></html>", "Visible").await;
+        do_test("<html><body>Visible<template><p>Hidden</p></template></body></html>", "Visible").await;
+        do_test("<html><body>Visible<img hidden src=\"a.png\"> Text</body></html>", "Visible Text").await;
+        do_test("<html><body>Visible<a title=\"a > b\" hidden>Hidden</a></body></html>", "Visible").await;
+        do_test("<html><body>Visible <noscript>Fallback</noscript></body></html>", "Visible Fallback").await;
+
+        let parameters = ExtractParameters { skip_noscript: true, ..Default::default() };
+        do_test_with_parameters("<html><body>Visible <noscript>Fallback</noscript></body></html>", "Visible", &parameters).await;
+
+        let parameters = ExtractParameters { skip_hidden: false, skip_aria_hidden: false, ..Default::default() };
+        do_test_with_parameters("<html><body>Visible <div hidden>Hidden</div></body></html>", "Visible\nHidden", &parameters).await;
+        do_test_with_parameters("<html><body>Visible <span aria-hidden=\"true\">Hidden</span></body></html>", "Visible Hidden", &parameters).await;
+    }
+
+    #[tokio::test]
+    async fn test_main_content() {
+        let page = "<html><head><title>Site</title></head><body>\
+            <header class=\"site-header\"><a href=\"/\">Home</a> <a href=\"/news\">News</a> <a href=\"/about\">About</a></header>\
+            <div class=\"cookie-banner\">We use cookies to improve your experience on our website.</div>\
+            <main><article><h1>Article title</h1>\
+            <p>The first paragraph of the article contains enough words to be recognised as the main content of the page.</p>\
+            <p>The second paragraph continues the story with some more sentences and a <a href=\"/link\">link</a> in it.</p>\
+            </article></main>\
+            <aside><ul><li><a href=\"/1\">Related story one</a></li><li><a href=\"/2\">Related story two</a></li></ul></aside>\
+            <footer>Copyright 2024 Example Publishing. All rights reserved.</footer></body></html>";
+
+        let parameters = ExtractParameters { content_mode: ContentMode::Main, ..Default::default() };
+        do_test_with_parameters(page, "Article title\nThe first paragraph of the article contains enough words to be recognised as the main content of the page.\nThe second paragraph continues the story with some more sentences and a link in it.", &parameters).await;
+
+        let parameters = ExtractParameters { content_mode: ContentMode::Main, output_format: OutputFormat::Advanced, ..Default::default() };
+        let fragments = extract(page, &parameters).await.results;
+        assert_eq!(fragments.len(), 3);
+        assert!(fragments.iter().all(|fragment| fragment.score.is_some()));
+    }
+
+    #[tokio::test]
+    async fn test_selectors() {
+        let page = "<html><head><title>Title</title></head><body><nav><a href=\"/\">Home</a></nav>\
+            <article id=\"story\"><div class=\"content main\">Story <span class=\"ads\">Advertisement</span>text</div>\
+            <div class=\"comments\"><div class=\"content\">Comment</div></div></article>\
+            <div class=\"content\">Other</div><p data-role=\"note\">Note</p></body></html>";
+
+        let parameters = ExtractParameters { include: Some(String::from("article .content")), ..Default::default() };
+        do_test_with_parameters(page, "\nStory Advertisementtext\nComment", &parameters).await;
+
+        let parameters = ExtractParameters { include: Some(String::from("#story > .content")), exclude: Some(String::from(".ads")), ..Default::default() };
+        do_test_with_parameters(page, "\nStory text", &parameters).await;
+
+        let parameters = ExtractParameters { exclude: Some(String::from(".ads, nav, article .comments, [data-role=note]")), ..Default::default() };
+        do_test_with_parameters(page, "Title\nStory text\nOther", &parameters).await;
+
+        let parameters = ExtractParameters { include: Some(String::from(".content")), exclude: Some(String::from(".comments")), ..Default::default() };
+        do_test_with_parameters(page, "\nStory Advertisementtext\nOther", &parameters).await;
+        let parameters = ExtractParameters { include: Some(String::from(".text")), ..Default::default() };
+        do_test_with_parameters("<html><body><div hidden><p class=\"text\">Hidden</p></div><p class=\"text\">Shown</p></body></html>", "\nShown", &parameters).await;
+
+        let parameters = ExtractParameters { include: Some(String::from("p[data-role^=no]")), ..Default::default() };
+        do_test_with_parameters(page, "\nNote", &parameters).await;
+
+        let parameters = ExtractParameters { include: Some(String::from("a + b")), ..Default::default() };
+        let extracted_text = extract(page, &parameters).await;
+        assert!(extracted_text.error.is_some_and(|error| error.starts_with("Invalid selector")));
+    }
+
+    // Handler writing the alternate text of images and removing digits from the text
+    struct TestHandler {}
+
+    impl ElementHandler for TestHandler {
+        fn on_start_element(&mut self, element: &Element, context: &mut HandlerContext) -> Option<bool> {
+            if element.name == "img" && context.elements.last().is_some_and(|parent| parent.name == "figure") {
+                element.attribute("alt").unwrap_or("").chars().for_each(|ch| context.write(ch));
+            }
+            (element.name == "script").then_some(true)
+        }
+
+        fn on_text(&mut self, ch: char, _context: &mut HandlerContext) -> bool {
+            ch.is_ascii_digit()
+        }
+    }
+
+    #[tokio::test]
+    async fn test_element_handler() {
+        let mut extractor = HtmlTextExtractor::new();
+        extractor.register_handler(Box::new(TestHandler {}));
+
+        let input = "<html><body>Figure 1: <figure><img alt=\"A cat\" src=\"cat.png\"></figure><script>Script</script></body></html>";
+        let mut output = Vec::new();
+        extractor.extract(&mut input.as_bytes(), &mut output, &ExtractParameters::default()).await.unwrap();
+        let extracted_text: ExtractedText = serde_json::from_slice(&output).unwrap();
+
+        assert_eq!(extracted_text.results[0].text, "Figure :\nA cat\nScript");
+    }
+
+    #[tokio::test]
+    async fn test_profile() {
+        let profile: Profile = serde_yaml::from_str("
+include: [article]
+exclude: ['.ads']
+blocks:
+  - selector: '.note'
+    separator: note
+attributes:
+  - selector: img
+    attribute: alt
+    format: '[{}]'
+  - selector: 'a[href^=\"http\"]'
+    attribute: href
+    format: ' ({})'
+    position: after
+").unwrap();
+
+        let input = "<html><body><nav>Menu</nav><article>Text <span class=\"note\">Note</span> <img alt=\"Image\" src=\"a.png\">\
+            <a href=\"https://example.com\">Link</a><div class=\"ads\"><img alt=\"Ad\"></div></article></body></html>";
+        let parameters = ExtractParameters { output_format: OutputFormat::Advanced, ..Default::default() };
+        let mut output = Vec::new();
+        HtmlTextExtractor::with_profile(Some(profile)).extract(&mut input.as_bytes(), &mut output, &parameters).await.unwrap();
+        let extracted_text: ExtractedText = serde_json::from_slice(&output).unwrap();
+
+        let texts: Vec<(Option<&str>, &str)> = extracted_text.results.iter().map(|fragment| (fragment.separator.as_deref(), fragment.text.as_str())).collect();
+        assert_eq!(texts, vec![(None, ""), (Some("<article>"), "Text"), (Some("note"), "Note"), (Some("</span>"), "[Image]Link (https://example.com)")]);
+    }
+
+    #[tokio::test]
+    async fn test_sections() {
+        let input = "<html><body><p>Intro</p><h1>Title</h1><p>Text 1</p><h2>Chapter 1</h2><p>Text 2</p><h3>Part</h3><p>Text 3</p>\
+            <h2>Chapter 2</h2><section><h3>Section</h3><p>Text 4</p></section><p>Text 5</p></body></html>";
+        let parameters = ExtractParameters { output_format: OutputFormat::Sections, ..Default::default() };
+        let output = extract_json(input, &parameters).await;
+
+        assert_eq!(output["input_format"], "html");
+        assert_eq!(output["results"], serde_json::json!([
+            { "level": 0, "blocks": ["Intro"], "sections": [] },
+            { "heading": "Title", "level": 1, "blocks": ["Text 1"], "sections": [
+                { "heading": "Chapter 1", "level": 2, "blocks": ["Text 2"], "sections": [
+                    { "heading": "Part", "level": 3, "blocks": ["Text 3"], "sections": [] }
+                ] },
+                { "heading": "Chapter 2", "level": 2, "blocks": ["Text 5"], "sections": [
+                    { "heading": "Section", "level": 3, "blocks": ["Text 4"], "sections": [] }
+                ] }
+            ] }
+        ]));
+    }
+
+    #[tokio::test]
+    async fn test_chunks() {
+        let input = "<html><body><h1>Guide</h1><p>First sentence here. Second sentence here.</p><h2>Setup</h2>\
+            <p>Install it. Run it now.</p></body></html>";
+        let parameters = ExtractParameters { output_format: OutputFormat::Chunks, chunk_size: 8, chunk_unit: ChunkUnit::Words, ..Default::default() };
+        assert_eq!(extract_json(input, &parameters).await["results"], serde_json::json!([
+            { "text": "Guide\nFirst sentence here. Second sentence here.", "start": 1, "end": 49, "size": 7, "headings": ["Guide"] },
+            { "text": "Setup\nInstall it. Run it now.", "start": 50, "end": 79, "size": 6, "headings": ["Guide", "Setup"] }
+        ]));
+    }
+
+    #[tokio::test]
+    async fn test_tables() {
+        let input = "<html><body><p>Prices</p><table><caption>Fruits</caption><thead><tr><th>Name<th>Size<th>Price</tr></thead>\
+            <tbody><tr><td rowspan=\"2\">Apple</td><td>Small</td><td>1</td></tr><tr><td>Large</td><td>2</td></tr>\
+            <tr><td colspan=\"2\">Pear | Any</td><td>3</td></tr></tbody></table></body></html>";
+
+        let parameters = ExtractParameters { tables: true, ..Default::default() };
+        let output = extract_json(input, &parameters).await;
+
+        assert_eq!(output["results"][0]["text"], "\nPrices\nFruits\nName\nSize\nPrice\nApple\nSmall\n1\nLarge\n2\nPear | Any\n3");
+        assert_eq!(output["tables"], serde_json::json!([{
+            "caption": "Fruits",
+            "header": [["Name", "Size", "Price"]],
+            "rows": [["Apple", "Small", "1"], ["Apple", "Large", "2"], ["Pear | Any", "Pear | Any", "3"]]
+        }]));
+
+        let parameters = ExtractParameters { table_format: TableFormat::Markdown, ..Default::default() };
+        do_test_with_parameters(input, "\nPrices\nFruits\n| Name | Size | Price |\n| --- | --- | --- |\n| Apple | Small | 1 |\n| Apple | Large | 2 |\n| Pear \\| Any | Pear \\| Any | 3 |", &parameters).await;
+
+        // The caption and the text outside of the cells are separate text blocks before the rendered table
+        let input = "<html><body><table><caption>Fruits</caption>Updated daily<tr><td>Apple</td><td>1</td></tr></table></body></html>";
+        let parameters = ExtractParameters { output_format: OutputFormat::Advanced, table_format: TableFormat::Tsv, ..Default::default() };
+        let fragments = extract(input, &parameters).await.results;
+        let texts: Vec<(Option<&str>, &str)> = fragments.iter().map(|fragment| (fragment.separator.as_deref(), fragment.text.as_str())).collect();
+        assert_eq!(texts, vec![(None, ""), (Some("<table>"), "Updated daily"), (Some("<caption>"), "Fruits"), (Some("<table>"), "Apple\t1")]);
+    }
+
+    #[tokio::test]
+    async fn test_lists() {
+        let markers = ExtractParameters { list_markers: true, ..Default::default() };
+        do_test_with_parameters("<html><body><ul><li>One<ul><li>Nested<ul><li>Deep</li></ul></li></ul></li><li><p>Two</p></li><li></li></ul>After</body></html>",
+            "\n• One\n  ◦ Nested\n    ▪ Deep\n• Two\nAfter", &markers).await;
+        do_test_with_parameters("<html><body><ol start=\"3\"><li>Three<li value=\"7\">Seven<li>Eight</ol></body></html>", "\n3. Three\n7. Seven\n8. Eight", &markers).await;
+        do_test_with_parameters("<html><body><ol type=\"i\"><li>One<li>Two<li>Three<li>Four</ol><ol type=\"A\" start=\"27\"><li>Item</ol></body></html>",
+            "\ni. One\nii. Two\niii. Three\niv. Four\nAA. Item", &markers).await;
+        do_test_with_parameters("<html><body><ol reversed><li>Three<li>Two<li>One</ol></body></html>", "\n3. Three\n2. Two\n1. One", &markers).await;
+        do_test_with_parameters("<html><body><dl><dt>Term</dt><dd>Definition</dd></dl></body></html>", "\nTerm\n: Definition", &markers).await;
+
+        // Reversed lists are numbered even if the document ends before their close tag
+        do_test_with_parameters("<html><body><ol reversed><li>Two<li>One", "\n2. Two\n1. One", &markers).await;
+
+        let parameters = ExtractParameters { output_format: OutputFormat::Advanced, list_markers: true, bullets: String::from("-"), ..Default::default() };
+        let fragments = extract("<html><body><ul><li>One</li><li>Two<ol reversed><li>A<li>B</ol></li></ul></body></html>", &parameters).await.results;
+        let texts: Vec<&str> = fragments.iter().map(|fragment| fragment.text.as_str()).collect();
+        assert_eq!(texts, vec!["", "- One", "- Two", "  2. A", "  1. B"]);
+
+        // Statistics of the items of reversed lists are counted from their final text
+        let parameters = ExtractParameters { output_format: OutputFormat::Advanced, list_markers: true, stats: true, ..Default::default() };
+        let output = extract_json("<html><body><ol reversed><li>Second<li>First</ol></body></html>", &parameters).await;
+        assert_eq!(output["results"][1]["text"], "2. Second");
+        assert_eq!(output["results"][1]["stats"]["characters"], 9);
+
+        do_test("<html><body><ul><li>One</li><li>Two</li></ul></body></html>", "\nOne\nTwo").await;
+    }
+
+    #[tokio::test]
+    async fn test_preformatted() {
+        do_test("<html><body><p>Code:</p><pre>\nfn main() {\r\n    println!(\"&lt;Hello&gt;\");\n}</pre><p>After   text</p></body></html>",
+            "\nCode:\nfn main() {\n    println!(\"<Hello>\");\n}\nAfter text").await;
+        do_test("<html><body>Poem<div style=\"white-space: pre\">Roses are red,\n  violets are blue</div></body></html>",
+            "Poem\nRoses are red,\n  violets are blue").await;
+        do_test("<html><body><textarea>\n\nLine  2</textarea></body></html>", "\n\nLine  2").await;
+
+        let parameters = ExtractParameters { output_format: OutputFormat::Advanced, ..Default::default() };
+        let output = extract("<html><body><p>Text</p><pre><code>let  x = 1;</code></pre></body></html>", &parameters).await;
+        let texts: Vec<(&str, bool)> = output.results.iter().map(|fragment| (fragment.text.as_str(), fragment.code)).collect();
+        assert_eq!(texts, vec![("", false), ("Text", false), ("let  x = 1;", true)]);
+
+        // Tags inside textarea and plaintext elements are part of their text
+        do_test("<html><body><textarea><b>Not bold</b> &amp; </p></textarea><p>After</p></body></html>", "\n<b>Not bold</b> & </p>\nAfter").await;
+        do_test("<html><body><p>Before</p><plaintext><b>Raw</b> &amp; </plaintext>", "\nBefore\n<b>Raw</b> &amp; </plaintext>").await;
+
+        // Line breaks are normalized to new line characters
+        do_test("<html><body><pre>One\rTwo\r\nThree\n\rFour</pre></body></html>", "\nOne\nTwo\nThree\n\nFour").await;
+
+        // Inline text with preserved whitespace is not code
+        let output = extract("<html><body><p>Poem: <span style=\"white-space: pre\">a  b</span></p><textarea>x  y</textarea></body></html>", &parameters).await;
+        let texts: Vec<(&str, bool)> = output.results.iter().map(|fragment| (fragment.text.as_str(), fragment.code)).collect();
+        assert_eq!(texts, vec![("", false), ("Poem: a  b", false), ("x  y", false)]);
+    }
+
     async fn do_test(input: &str, expected_output: &str) {
-        let request_body = Body::from(String::from(input));
-        let response_body = BodyTextExtractor::extract(request_body, OutputFormat::Simple).await;
-        let response_bytes = to_bytes(response_body, usize::MAX).await.unwrap();
-        let output = std::str::from_utf8(&response_bytes).unwrap();
-        let extracted_text: ExtractedText = serde_json::from_str(output).unwrap();
+        do_test_with_parameters(input, expected_output, &ExtractParameters::default()).await;
+    }
+
+    async fn do_test_with_parameters(input: &str, expected_output: &str, parameters: &ExtractParameters) {
+        let extracted_text = extract(input, parameters).await;
         let text = &extracted_text.results[0].text;
 
         assert_eq!(text, expected_output);
     }
+
+    async fn extract(input: &str, parameters: &ExtractParameters) -> ExtractedText {
+        serde_json::from_value(extract_json(input, parameters).await).unwrap()
+    }
+
+    async fn extract_json(input: &str, parameters: &ExtractParameters) -> serde_json::Value {
+        let request_body = Body::from(String::from(input));
+        let response_body = BodyTextExtractor::extract(request_body, parameters.clone(), None).await;
+        let response_bytes = to_bytes(response_body, usize::MAX).await.unwrap();
+        serde_json::from_slice(&response_bytes).unwrap()
+    }
 }
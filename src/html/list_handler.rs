@@ -0,0 +1,180 @@
+use super::{element::Element, element_handler::{ElementHandler, HandlerContext}};
+
+// Handler writing bullets and numbers before list items, and indenting the items of nested lists and definitions
+#[derive(Default)]
+pub struct ListHandler {
+    // Open lists (the innermost is the last one)
+    lists: Vec<List>,
+    // Number of lists numbered at their end, used to create unique placeholders of the numbers
+    deferred_list_count: usize,
+}
+
+enum List {
+    Unordered { bullet: Option<char> },
+    Ordered { style: NumberStyle, next: i64, step: i64, deferred: Option<DeferredNumbers> },
+    Definition,
+}
+
+#[derive(Clone, Copy)]
+enum NumberStyle { Decimal, LowerAlpha, UpperAlpha, LowerRoman, UpperRoman }
+
+// Numbers of reversed lists without start attribute depend on the number of items, therefore placeholders are
+// written and they are replaced at the end of the list
+struct DeferredNumbers {
+    id: usize,
+    // Values set by the value attribute of the items
+    values: Vec<Option<i64>>,
+}
+
+impl ListHandler {
+    fn indentation(&self) -> String {
+        "  ".repeat(self.lists.len().saturating_sub(1))
+    }
+
+    fn marker(&mut self, element: &Element, context: &HandlerContext) -> Option<String> {
+        let level = self.lists.len();
+        let value = element.attribute("value").and_then(|value| value.trim().parse::<i64>().ok());
+        match self.lists.last_mut()? {
+            List::Unordered { bullet } => {
+                let bullets: Vec<char> = context.parameters.bullets.chars().collect();
+                bullet.or_else(|| bullets.get(level - 1).or(bullets.last()).copied()).map(String::from)
+            }
+            List::Ordered { style, next, step, deferred } => {
+                if let Some(deferred) = deferred {
+                    deferred.values.push(value);
+                    return Some(format!("\u{E000}{}.{}\u{E001}", deferred.id, deferred.values.len() - 1));
+                }
+                let number = value.unwrap_or(*next);
+                *next = number + *step;
+                Some(format!("{}.", ListHandler::format_number(number, *style)))
+            }
+            List::Definition => None,
+        }
+    }
+
+    fn format_number(number: i64, style: NumberStyle) -> String {
+        match style {
+            NumberStyle::LowerAlpha | NumberStyle::UpperAlpha if number > 0 => {
+                let mut letters = Vec::new();
+                let mut rest = number;
+                while rest > 0 {
+                    rest -= 1;
+                    letters.push((b'a' + (rest % 26) as u8) as char);
+                    rest /= 26;
+                }
+                let letters: String = letters.iter().rev().collect();
+                if matches!(style, NumberStyle::UpperAlpha) { letters.to_uppercase() } else { letters }
+            }
+            NumberStyle::LowerRoman | NumberStyle::UpperRoman if (1..4000).contains(&number) => {
+                const NUMERALS: [(i64, &str); 13] = [
+                    (1000, "m"), (900, "cm"), (500, "d"), (400, "cd"), (100, "c"), (90, "xc"),
+                    (50, "l"), (40, "xl"), (10, "x"), (9, "ix"), (5, "v"), (4, "iv"), (1, "i"),
+                ];
+                let mut numerals = String::new();
+                let mut rest = number;
+                for (value, numeral) in NUMERALS {
+                    while rest >= value {
+                        numerals.push_str(numeral);
+                        rest -= value;
+                    }
+                }
+                if matches!(style, NumberStyle::UpperRoman) { numerals.to_uppercase() } else { numerals }
+            }
+            _ => number.to_string(),
+        }
+    }
+
+    fn ordered_list(&mut self, element: &Element, context: &mut HandlerContext) -> List {
+        let style = match element.attribute("type") {
+            Some("a") => NumberStyle::LowerAlpha,
+            Some("A") => NumberStyle::UpperAlpha,
+            Some("i") => NumberStyle::LowerRoman,
+            Some("I") => NumberStyle::UpperRoman,
+            _ => NumberStyle::Decimal,
+        };
+        let start = element.attribute("start").and_then(|value| value.trim().parse::<i64>().ok());
+        let reversed = element.has_attribute("reversed");
+
+        let deferred = (reversed && start.is_none()).then(|| {
+            context.output.hold();
+            self.deferred_list_count += 1;
+            DeferredNumbers { id: self.deferred_list_count, values: Vec::new() }
+        });
+        List::Ordered { style, next: start.unwrap_or(1), step: if reversed { -1 } else { 1 }, deferred }
+    }
+
+    // Replace the placeholders of a reversed list, which starts from the number of its items
+    fn release(deferred: DeferredNumbers, style: NumberStyle, context: &mut HandlerContext) {
+        let mut next = deferred.values.len() as i64;
+        let replacements: Vec<(String, String)> = deferred
+            .values
+            .iter()
+            .enumerate()
+            .map(|(index, value)| {
+                let number = value.unwrap_or(next);
+                next = number - 1;
+                (format!("\u{E000}{}.{}\u{E001}", deferred.id, index), format!("{}.", ListHandler::format_number(number, style)))
+            })
+            .collect();
+        context.output.release(&replacements);
+    }
+}
+
+impl ElementHandler for ListHandler {
+    fn on_start_element(&mut self, element: &Element, context: &mut HandlerContext) -> Option<bool> {
+        match element.name.as_str() {
+            "ul" | "menu" => {
+                let bullet = match element.attribute("type").map(|value| value.to_ascii_lowercase()).as_deref() {
+                    Some("disc") => Some('•'),
+                    Some("circle") => Some('◦'),
+                    Some("square") => Some('▪'),
+                    _ => None,
+                };
+                self.lists.push(List::Unordered { bullet });
+            }
+            "ol" => {
+                let list = self.ordered_list(element, context);
+                self.lists.push(list);
+            }
+            "dl" => self.lists.push(List::Definition),
+            // Items of hidden lists are not counted
+            "li" if context.output_enabled => {
+                let prefix = match self.marker(element, context) {
+                    Some(marker) => format!("{}{} ", self.indentation(), marker),
+                    None => self.indentation(),
+                };
+                context.output.set_prefix((!prefix.is_empty()).then_some(prefix));
+            }
+            // Definitions are written in separate lines after their terms
+            "dt" => {
+                let prefix = self.indentation();
+                context.output.set_prefix((!prefix.is_empty()).then_some(prefix));
+            }
+            "dd" => context.output.set_prefix(Some(format!("{}: ", self.indentation()))),
+            _ => {}
+        }
+        None
+    }
+
+    fn on_end_element(&mut self, element: &Element, context: &mut HandlerContext) {
+        match element.name.as_str() {
+            "ul" | "menu" | "ol" | "dl" => {
+                if let Some(List::Ordered { style, deferred: Some(deferred), .. }) = self.lists.pop() {
+                    ListHandler::release(deferred, style, context);
+                }
+            }
+            // Prefix of empty items is not written before the next text
+            "li" | "dt" | "dd" => context.output.set_prefix(None),
+            _ => {}
+        }
+    }
+
+    // Reversed lists not closed before the end of the document are numbered by the items found
+    fn on_end_document(&mut self, context: &mut HandlerContext) {
+        while let Some(list) = self.lists.pop() {
+            if let List::Ordered { style, deferred: Some(deferred), .. } = list {
+                ListHandler::release(deferred, style, context);
+            }
+        }
+    }
+}
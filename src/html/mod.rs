@@ -1,7 +1,14 @@
+pub mod default_handlers;
+pub mod element;
+pub mod element_handler;
 pub mod html_text_extractor;
+pub mod profile;
 mod comment_parser;
 mod content_parser;
 mod element_parser;
 mod entity_parser;
+mod list_handler;
 mod parser_context;
-mod processor;
\ No newline at end of file
+mod raw_text_parser;
+mod selector;
+mod table_handler;
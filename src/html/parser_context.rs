@@ -1,41 +1,134 @@
-use crate::{error::Error, util::{json_writer::JsonWriter, utf8_reader::Utf8Reader}};
+use super::{element::Element, element_handler::{ElementHandler, HandlerContext}};
+use crate::{error::Error, text_extractor::ExtractParameters, util::{json_writer::JsonWriter, utf8_reader::Utf8Reader, utf8_writer::Utf8Writer}};
 use tokio::io::{AsyncRead, AsyncWrite};
 
 pub struct ParserContext<'a, R: AsyncRead + Unpin + Send, W: AsyncWrite + Unpin + Send> {
     pub input: &'a mut Utf8Reader<'a, R>,
-    pub output: &'a mut JsonWriter<'a, W>,
+    pub output: JsonWriter,
+    writer: Utf8Writer<'a, W>,
     pub buffer: String,
-    pub output_enabled: bool
+    pub output_enabled: bool,
+    // Whether the last character of preformatted text was a carriage return
+    after_carriage_return: bool,
+    // Elements opened but not closed yet (the innermost is the last one)
+    pub elements: Vec<Element>,
+    handlers: Vec<Box<dyn ElementHandler + 'a>>,
+    pub parameters: &'a ExtractParameters
 }
 
 impl<'a, R: AsyncRead + Unpin + Send, W: AsyncWrite + Unpin + Send> ParserContext<'a, R, W> {
     pub fn new(
         input: &'a mut Utf8Reader<'a, R>,
-        output: &'a mut JsonWriter<'a, W>,
+        writer: Utf8Writer<'a, W>,
         buffer: String,
+        handlers: Vec<Box<dyn ElementHandler + 'a>>,
+        parameters: &'a ExtractParameters,
     ) -> Self {
         Self {
             input,
-            output,
+            output: JsonWriter::new(parameters),
+            writer,
             buffer,
-            output_enabled: false
+            output_enabled: false,
+            after_carriage_return: false,
+            elements: Vec::new(),
+            handlers,
+            parameters
         }
     }
 
+    // Write a character of the text content to the output, unless an element handler handles it
     #[inline]
     pub async fn write(&mut self, ch: char) -> Result<(), Error> {
-        if self.output_enabled {
-            self.output.write_char(ch).await?;
+        let mut handler_context = HandlerContext {
+            elements: &self.elements,
+            output: &mut self.output,
+            output_enabled: self.output_enabled,
+            parameters: self.parameters,
+        };
+        if !self.handlers.iter_mut().any(|handler| handler.on_text(ch, &mut handler_context)) {
+            handler_context.write(ch);
         }
-        Ok(())
+        self.flush().await
     }
 
-    pub async fn start(&mut self) -> Result<(), Error> {    
-        self.output.start().await
+    // Write a character of the document text. Whitespaces in HTML source are usually not displayed on the page except
+    // one space between words, while line breaks (CR LF and CR) are normalized to new line characters inside
+    // preformatted text.
+    pub async fn write_text(&mut self, ch: char) -> Result<(), Error> {
+        let after_carriage_return = std::mem::replace(&mut self.after_carriage_return, ch == '\r');
+        if self.output.is_preformatted() {
+            match ch {
+                '\r' => self.write('\n').await,
+                '\n' if after_carriage_return => Ok(()),
+                ch => self.write(ch).await,
+            }
+        } else if ch.is_whitespace() {
+            self.write(' ').await
+        } else {
+            self.write(ch).await
+        }
+    }
+
+    // Call the element handlers when the open tag of an element is found and return whether to extract its content
+    pub async fn on_start_element(&mut self, element: &Element) -> Result<Option<bool>, Error> {
+        let mut output_enabled = None;
+        let mut handler_context = HandlerContext {
+            elements: &self.elements,
+            output: &mut self.output,
+            output_enabled: self.output_enabled,
+            parameters: self.parameters,
+        };
+        for handler in self.handlers.iter_mut() {
+            if let Some(value) = handler.on_start_element(element, &mut handler_context) {
+                output_enabled = Some(value);
+                handler_context.output_enabled = value;
+            }
+        }
+        self.flush().await?;
+        Ok(output_enabled)
+    }
+
+    // Call the element handlers when an element is closed
+    pub async fn on_end_element(&mut self, element: &Element) -> Result<(), Error> {
+        let mut handler_context = HandlerContext {
+            elements: &self.elements,
+            output: &mut self.output,
+            output_enabled: self.output_enabled,
+            parameters: self.parameters,
+        };
+        for handler in self.handlers.iter_mut() {
+            handler.on_end_element(element, &mut handler_context);
+        }
+        self.flush().await
+    }
+
+    pub async fn start(&mut self) -> Result<(), Error> {
+        self.output.start();
+        self.flush().await
     }
 
-    pub async fn end(&mut self, message: &str) -> Result<(), Error> {    
-        self.output.end(message).await
+    pub async fn end(&mut self, message: &str) -> Result<(), Error> {
+        let mut handler_context = HandlerContext {
+            elements: &self.elements,
+            output: &mut self.output,
+            output_enabled: self.output_enabled,
+            parameters: self.parameters,
+        };
+        for handler in self.handlers.iter_mut() {
+            handler.on_end_document(&mut handler_context);
+        }
+        self.output.end(message);
+        self.flush().await
     }
 
+    // Write the JSON serialized by the output to the output stream. It is done once per completed fragment, the
+    // characters written into the current fragment are not serialized yet.
+    async fn flush(&mut self) -> Result<(), Error> {
+        if !self.output.has_output() {
+            return Ok(());
+        }
+        let json = self.output.take_output();
+        self.writer.write_string(&json).await
+    }
 }
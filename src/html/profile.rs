@@ -0,0 +1,186 @@
+use super::{
+    default_handlers::default_handlers,
+    element::Element,
+    element_handler::{ElementHandler, HandlerContext},
+    selector::SelectorList,
+};
+use crate::{error::Error, text_extractor::ExtractParameters};
+use serde::Deserialize;
+use std::{collections::HashMap, path::Path};
+
+// Extraction rules of a site loaded from a YAML or JSON file. Rules are applied in addition to the default behaviour.
+#[derive(Deserialize, Clone, Default)]
+pub struct Profile {
+    // Selectors of elements to extract content from
+    #[serde(default)]
+    pub include: Vec<String>,
+    // Selectors of elements to exclude from the output
+    #[serde(default)]
+    pub exclude: Vec<String>,
+    // Elements starting a new text block
+    #[serde(default)]
+    pub blocks: Vec<BlockRule>,
+    // Attributes written to the output (like the alternate text of images)
+    #[serde(default)]
+    pub attributes: Vec<AttributeRule>,
+}
+
+#[derive(Deserialize, Clone)]
+pub struct BlockRule {
+    pub selector: String,
+    // Separator of the block in the Advanced output (the open tag of the element by default)
+    pub separator: Option<String>,
+}
+
+#[derive(Deserialize, Clone, Copy, PartialEq, Default)]
+#[serde(rename_all = "lowercase")]
+pub enum AttributePosition {
+    #[default]
+    Before,
+    After,
+}
+
+#[derive(Deserialize, Clone)]
+pub struct AttributeRule {
+    pub selector: String,
+    pub attribute: String,
+    // Text written to the output, where {} is replaced by the value of the attribute
+    #[serde(default = "default_format")]
+    pub format: String,
+    // Write the attribute before or after the content of the element
+    #[serde(default)]
+    pub position: AttributePosition,
+}
+
+fn default_format() -> String {
+    String::from("{}")
+}
+
+impl Profile {
+    // Load all profiles from a directory. Name of a profile is the name of its file without the extension. Invalid
+    // profiles are reported on the standard error and skipped, so they do not prevent using the others.
+    pub fn load_all(directory: &Path) -> Result<HashMap<String, Profile>, Error> {
+        let mut profiles = HashMap::new();
+        for entry in std::fs::read_dir(directory)? {
+            let path = entry?.path();
+            let Some(name) = path.file_stem() else {
+                continue;
+            };
+            match Profile::load(&path) {
+                Ok(Some(profile)) => {
+                    profiles.insert(name.to_string_lossy().into_owned(), profile);
+                }
+                Ok(None) => {}
+                Err(error) => eprintln!("{}", error.message),
+            }
+        }
+        Ok(profiles)
+    }
+
+    // Load a profile from a YAML or JSON file (files with other extensions are not profiles)
+    pub fn load(path: &Path) -> Result<Option<Profile>, Error> {
+        let parse = match path.extension().and_then(|extension| extension.to_str()) {
+            Some("yaml" | "yml") => |source: &str| serde_yaml::from_str::<Profile>(source).map_err(|error| error.to_string()),
+            Some("json") => |source: &str| serde_json::from_str::<Profile>(source).map_err(|error| error.to_string()),
+            _ => return Ok(None),
+        };
+        let source = std::fs::read_to_string(path).map_err(|error| Profile::error(path, &error.to_string()))?;
+        let profile = parse(&source).map_err(|message| Profile::error(path, &message))?;
+
+        // Rules are checked when they are loaded to find invalid selectors before the first request
+        default_handlers(&ExtractParameters::default(), Some(&profile)).map_err(|error| Profile::error(path, &error.message))?;
+        Ok(Some(profile))
+    }
+
+    fn error(path: &Path, message: &str) -> Error {
+        Error { eof: false, message: format!("Invalid profile {}: {}", path.display(), message) }
+    }
+}
+
+// Element handler applying the block and attribute rules of a profile (include and exclude rules are applied by the SelectorHandler)
+pub struct ProfileHandler {
+    blocks: Vec<(SelectorList, Option<String>)>,
+    attributes: Vec<(SelectorList, AttributeRule)>,
+}
+
+impl ProfileHandler {
+    pub fn new(profile: &Profile) -> Result<Self, Error> {
+        Ok(Self {
+            blocks: profile
+                .blocks
+                .iter()
+                .map(|rule| Ok((SelectorList::parse(&rule.selector)?, rule.separator.clone())))
+                .collect::<Result<_, Error>>()?,
+            attributes: profile
+                .attributes
+                .iter()
+                .map(|rule| Ok((SelectorList::parse(&rule.selector)?, rule.clone())))
+                .collect::<Result<_, Error>>()?,
+        })
+    }
+
+    fn write_attributes(&self, element: &Element, context: &mut HandlerContext, position: AttributePosition) {
+        for (selector, rule) in self.attributes.iter().filter(|(_, rule)| rule.position == position) {
+            if let Some(value) = element.attribute(&rule.attribute).filter(|value| !value.is_empty()) {
+                if selector.matches(element, context.elements) {
+                    rule.format.replace("{}", value).chars().for_each(|ch| context.write(ch));
+                }
+            }
+        }
+    }
+}
+
+impl ElementHandler for ProfileHandler {
+    fn on_start_element(&mut self, element: &Element, context: &mut HandlerContext) -> Option<bool> {
+        if let Some((_, separator)) = self.blocks.iter().find(|(selector, _)| selector.matches(element, context.elements)) {
+            let separator = separator.clone().unwrap_or_else(|| format!("<{}>", element.name));
+            context.output.add_break(&separator);
+        }
+
+        self.write_attributes(element, context, AttributePosition::Before);
+        None
+    }
+
+    fn on_end_element(&mut self, element: &Element, context: &mut HandlerContext) {
+        self.write_attributes(element, context, AttributePosition::After);
+
+        if self.blocks.iter().any(|(selector, _)| selector.matches(element, context.elements)) {
+            context.output.add_break(&format!("</{}>", element.name));
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_load_profiles() {
+        let directory = std::env::temp_dir().join(format!("textminer-profiles-{}", std::process::id()));
+        std::fs::create_dir_all(&directory).unwrap();
+        std::fs::write(directory.join("valid.yaml"), "include: [article]").unwrap();
+        std::fs::write(directory.join("other.yml"), "exclude: ['a[title=\"x,y\"], .ads']").unwrap();
+        std::fs::write(directory.join("site.json"), "{\"blocks\": [{\"selector\": \".note\", \"separator\": \"note\"}]}").unwrap();
+        std::fs::write(directory.join("malformed.yaml"), "include: [article").unwrap();
+        std::fs::write(directory.join("selector.json"), "{\"exclude\": [\"[\"]}").unwrap();
+        std::fs::write(directory.join("readme.txt"), "Not a profile").unwrap();
+
+        // Invalid profiles are skipped and reported with their file, while the valid ones are loaded
+        let profiles = Profile::load_all(&directory);
+        let malformed = Profile::load(&directory.join("malformed.yaml"));
+        let selector = Profile::load(&directory.join("selector.json"));
+        let readme = Profile::load(&directory.join("readme.txt"));
+        std::fs::remove_dir_all(&directory).unwrap();
+
+        let profiles = profiles.unwrap();
+        let mut names: Vec<&str> = profiles.keys().map(String::as_str).collect();
+        names.sort();
+        assert_eq!(names, vec!["other", "site", "valid"]);
+        assert_eq!(profiles["valid"].include, vec!["article"]);
+        assert_eq!(profiles["site"].blocks[0].separator.as_deref(), Some("note"));
+        assert!(malformed.is_err_and(|error| error.message.starts_with("Invalid profile") && error.message.contains("malformed.yaml")));
+        assert!(selector.is_err_and(|error| error.message.contains("Invalid selector")));
+        assert!(readme.is_ok_and(|profile| profile.is_none()));
+        assert!(Profile::load_all(&directory).is_err());
+    }
+}
@@ -0,0 +1,67 @@
+use super::{entity_parser::EntityParser, parser_context::ParserContext};
+use crate::error::Error;
+use tokio::io::{AsyncRead, AsyncWrite};
+
+// Parse the content of elements containing text only (tags inside them are part of the text)
+pub struct RawTextParser {}
+
+impl RawTextParser {
+    // Method is called when the open tag of a textarea or plaintext element was processed. Content of a textarea ends
+    // at its close tag, while the content of a plaintext element continues until the end of the document.
+    pub async fn parse<R: AsyncRead + Unpin + Send, W: AsyncWrite + Unpin + Send>(
+        context: &mut ParserContext<'_, R, W>,
+        name: &str,
+    ) -> Result<(), Error> {
+        let close_tag = format!("</{}", name);
+        // Character read after a text which turned out not to be the close tag
+        let mut next_char = None;
+        loop {
+            let ch = match next_char.take() {
+                Some(ch) => ch,
+                None => context.input.read_char().await?,
+            };
+            match ch {
+                '<' if name != "plaintext" => {
+                    // Characters are read while they can be the close tag, otherwise they are written as text
+                    let mut text = String::from('<');
+                    loop {
+                        let ch = context.input.read_char().await?;
+                        if text.len() == close_tag.len() {
+                            if ch == '>' || ch == '/' || ch.is_whitespace() {
+                                RawTextParser::skip_tag(context, ch).await?;
+                                return Ok(());
+                            }
+                            next_char = Some(ch);
+                            break;
+                        }
+                        if !close_tag[text.len()..].starts_with(ch.to_ascii_lowercase()) {
+                            next_char = Some(ch);
+                            break;
+                        }
+                        text.push(ch);
+                    }
+                    for ch in text.chars() {
+                        context.write_text(ch).await?;
+                    }
+                }
+                // Entities are decoded in the text of textarea elements
+                '&' if context.output_enabled && name != "plaintext" => {
+                    EntityParser::parse(context).await?;
+                    context.buffer.clear();
+                }
+                ch => context.write_text(ch).await?,
+            }
+        }
+    }
+
+    // Read the rest of the close tag (attributes of close tags are ignored)
+    async fn skip_tag<R: AsyncRead + Unpin + Send, W: AsyncWrite + Unpin + Send>(
+        context: &mut ParserContext<'_, R, W>,
+        mut ch: char,
+    ) -> Result<(), Error> {
+        while ch != '>' {
+            ch = context.input.read_char().await?;
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,252 @@
+use super::element::Element;
+use crate::error::Error;
+
+// A comma separated list of CSS selectors like 'article .content, #main > p'. Tag name, id, class and attribute
+// selectors are supported, combined by descendant and child combinators.
+pub struct SelectorList {
+    selectors: Vec<Selector>,
+}
+
+// Compound selectors of a complex selector with the combinators connecting them to the previous one
+struct Selector {
+    compounds: Vec<(Combinator, CompoundSelector)>,
+}
+
+#[derive(PartialEq, Clone, Copy)]
+enum Combinator {
+    Descendant,
+    Child,
+}
+
+#[derive(Default)]
+struct CompoundSelector {
+    tag: Option<String>,
+    id: Option<String>,
+    classes: Vec<String>,
+    attributes: Vec<AttributeSelector>,
+}
+
+struct AttributeSelector {
+    name: String,
+    operator: Option<char>,
+    value: String,
+}
+
+impl SelectorList {
+    pub fn parse(source: &str) -> Result<Self, Error> {
+        let selectors = SelectorList::split(source)
+            .into_iter()
+            .map(Selector::parse)
+            .collect::<Result<Vec<Selector>, Error>>()?;
+        Ok(Self { selectors })
+    }
+
+    // Split the list at the commas between the selectors. Commas of attribute selectors (like [title="x,y"]) are kept.
+    fn split(source: &str) -> Vec<&str> {
+        let mut parts = Vec::new();
+        let mut start = 0;
+        let mut in_brackets = false;
+        let mut quote = None;
+
+        for (index, ch) in source.char_indices() {
+            match (quote, ch) {
+                (Some(quote_char), ch) if ch == quote_char => quote = None,
+                (Some(_), _) => {}
+                (None, '"' | '\'') if in_brackets => quote = Some(ch),
+                (None, '[') => in_brackets = true,
+                (None, ']') => in_brackets = false,
+                (None, ',') if !in_brackets => {
+                    parts.push(&source[start..index]);
+                    start = index + 1;
+                }
+                _ => {}
+            }
+        }
+        parts.push(&source[start..]);
+        parts
+    }
+
+    // Check whether the element matches any of the selectors. Ancestors are the elements containing it, the parent is the last one.
+    pub fn matches(&self, element: &Element, ancestors: &[Element]) -> bool {
+        self.selectors.iter().any(|selector| selector.matches(element, ancestors))
+    }
+}
+
+impl Selector {
+    fn parse(source: &str) -> Result<Self, Error> {
+        let mut compounds = Vec::new();
+        let mut combinator = Combinator::Descendant;
+        let mut chars = source.trim().chars().peekable();
+
+        while chars.peek().is_some() {
+            let mut compound = CompoundSelector::default();
+            let mut empty = true;
+
+            while let Some(&ch) = chars.peek() {
+                match ch {
+                    '#' => {
+                        chars.next();
+                        compound.id = Some(Selector::parse_name(&mut chars));
+                    }
+                    '.' => {
+                        chars.next();
+                        compound.classes.push(Selector::parse_name(&mut chars));
+                    }
+                    '[' => {
+                        chars.next();
+                        compound.attributes.push(AttributeSelector::parse(&mut chars)?);
+                    }
+                    '*' => {
+                        chars.next();
+                    }
+                    ch if ch.is_whitespace() || ch == '>' => break,
+                    '+' | '~' | ':' => return Err(Selector::error(source, "sibling combinators and pseudo-classes are not supported")),
+                    _ => {
+                        let tag = Selector::parse_name(&mut chars);
+                        if tag.is_empty() {
+                            return Err(Selector::error(source, &format!("unexpected character '{}'", ch)));
+                        }
+                        compound.tag = Some(tag.to_lowercase());
+                    }
+                }
+                empty = false;
+            }
+
+            if empty {
+                return Err(Selector::error(source, "selector is empty"));
+            }
+            compounds.push((combinator, compound));
+
+            // Read the combinator between two compound selectors
+            combinator = Combinator::Descendant;
+            while let Some(&ch) = chars.peek() {
+                if ch == '>' {
+                    combinator = Combinator::Child;
+                } else if !ch.is_whitespace() {
+                    break;
+                }
+                chars.next();
+            }
+        }
+
+        if compounds.is_empty() {
+            return Err(Selector::error(source, "selector is empty"));
+        }
+        Ok(Self { compounds })
+    }
+
+    fn parse_name(chars: &mut std::iter::Peekable<std::str::Chars>) -> String {
+        let mut name = String::new();
+        while let Some(ch) = chars.next_if(|ch| ch.is_alphanumeric() || *ch == '-' || *ch == '_') {
+            name.push(ch);
+        }
+        name
+    }
+
+    fn error(source: &str, reason: &str) -> Error {
+        Error { eof: false, message: format!("Invalid selector '{}': {}", source.trim(), reason) }
+    }
+
+    fn matches(&self, element: &Element, ancestors: &[Element]) -> bool {
+        let (_, subject) = self.compounds.last().expect("Selector contains at least one compound selector.");
+        subject.matches(element) && self.matches_ancestors(self.compounds.len() - 1, ancestors)
+    }
+
+    // Check whether the compound selectors before the index match the ancestors. (Compound selector at the index was matched already.)
+    fn matches_ancestors(&self, index: usize, ancestors: &[Element]) -> bool {
+        if index == 0 {
+            return true;
+        }
+        let (combinator, _) = self.compounds[index];
+        let (_, compound) = &self.compounds[index - 1];
+
+        match combinator {
+            Combinator::Child => match ancestors.split_last() {
+                Some((parent, rest)) => compound.matches(parent) && self.matches_ancestors(index - 1, rest),
+                None => false,
+            },
+            Combinator::Descendant => (0..ancestors.len())
+                .rev()
+                .any(|position| compound.matches(&ancestors[position]) && self.matches_ancestors(index - 1, &ancestors[..position])),
+        }
+    }
+}
+
+impl CompoundSelector {
+    fn matches(&self, element: &Element) -> bool {
+        self.tag.as_ref().is_none_or(|tag| *tag == element.name)
+            && self.id.as_ref().is_none_or(|id| element.attribute("id") == Some(id.as_str()))
+            && self.classes.iter().all(|class| element.classes().any(|element_class| element_class == class))
+            && self.attributes.iter().all(|attribute| attribute.matches(element))
+    }
+}
+
+impl AttributeSelector {
+    // Parse an attribute selector like [name], [name=value] or [name^="value"] after the opening bracket
+    fn parse(chars: &mut std::iter::Peekable<std::str::Chars>) -> Result<Self, Error> {
+        let mut name = String::new();
+        let mut operator = None;
+        let mut value = String::new();
+        let mut quote = None;
+        let mut in_value = false;
+
+        loop {
+            let ch = chars.next().ok_or_else(|| Selector::error("[", "attribute selector is not closed"))?;
+            match (quote, ch) {
+                (Some(quote_char), ch) if ch == quote_char => quote = None,
+                (Some(_), ch) => value.push(ch),
+                (None, ']') => break,
+                (None, '"' | '\'') if in_value => quote = Some(ch),
+                (None, '=') => {
+                    if operator.is_none() {
+                        operator = Some('=');
+                    }
+                    in_value = true;
+                }
+                (None, '~' | '^' | '$' | '*' | '|') if !in_value => operator = Some(ch),
+                (None, ch) if ch.is_whitespace() => {}
+                (None, ch) if in_value => value.push(ch),
+                (None, ch) => name.push(ch.to_ascii_lowercase()),
+            }
+        }
+
+        Ok(Self { name, operator, value })
+    }
+
+    fn matches(&self, element: &Element) -> bool {
+        let Some(actual) = element.attribute(&self.name) else {
+            return false;
+        };
+        match self.operator {
+            None => true,
+            Some('=') => actual == self.value,
+            Some('~') => actual.split_whitespace().any(|word| word == self.value),
+            Some('^') => actual.starts_with(&self.value),
+            Some('$') => actual.ends_with(&self.value),
+            Some('*') => actual.contains(&self.value),
+            Some('|') => actual == self.value || actual.starts_with(&format!("{}-", self.value)),
+            Some(_) => false,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn matches(selectors: &str, element: &str, attributes: &str) -> bool {
+        let selector_list = SelectorList::parse(selectors).unwrap();
+        selector_list.matches(&Element::new(String::from(element), attributes), &[])
+    }
+
+    #[test]
+    fn test_selector_list() {
+        assert!(matches("a[title=\"x,y\"]", "a", r#" title="x,y""#));
+        assert!(!matches("a[title=\"x,y\"]", "a", r#" title="x""#));
+        assert!(matches("p, a[title='x,y'], div", "div", ""));
+        assert!(matches("p, a[title='x,y'], div", "a", r#" title="x,y""#));
+        assert_eq!(SelectorList::parse("a[title='x,y'], .note").unwrap().selectors.len(), 2);
+        assert!(SelectorList::parse("p,,div").is_err());
+        assert!(SelectorList::parse("a[title='x,y'").is_err());
+    }
+}
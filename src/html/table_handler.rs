@@ -0,0 +1,254 @@
+use super::{element::Element, element_handler::{ElementHandler, HandlerContext}};
+use crate::{text_extractor::TableFormat, util::table::Table};
+
+// Handler collecting the rows and cells of tables. Tables are added to the output and optionally rendered as one
+// text block instead of the text of their cells.
+#[derive(Default)]
+pub struct TableHandler {
+    // Open tables (nested tables are collected separately from the tables containing them)
+    tables: Vec<TableBuilder>,
+}
+
+// State of an open table
+#[derive(Default)]
+struct TableBuilder {
+    table: Table,
+    // Whether the table was included in the output when it was opened
+    enabled: bool,
+    caption: Option<String>,
+    // Text outside of the caption and the cells, which is written before the table like browsers do
+    text: String,
+    row: Option<Row>,
+    cell: Option<Cell>,
+    header_group: bool,
+    // Cells of the previous rows spanning into the next rows
+    row_spans: Vec<RowSpan>,
+}
+
+#[derive(Default)]
+struct Row {
+    cells: Vec<String>,
+    // Whether the row is in the thead element
+    header: bool,
+    // Whether the row contains td cells
+    data_cells: bool,
+    // Cells of the row spanning into the next rows
+    row_spans: Vec<RowSpan>,
+}
+
+struct Cell {
+    text: String,
+    colspan: usize,
+    rowspan: usize,
+    header: bool,
+}
+
+struct RowSpan {
+    column: usize,
+    // Number of next rows containing the cell
+    rows: usize,
+    text: String,
+}
+
+impl TableHandler {
+    // Text inside tables is written by the handler if tables are rendered as text blocks
+    fn renders_tables(context: &HandlerContext) -> bool {
+        context.parameters.table_format != TableFormat::Text
+    }
+
+    // Text outside of the cells and the caption are written as separate text blocks before the rendered table
+    fn render(table: &Table, text: &str, context: &mut HandlerContext) {
+        let rendered = match context.parameters.table_format {
+            TableFormat::Text => return,
+            TableFormat::Tsv => table.to_tsv(),
+            TableFormat::Markdown => table.to_markdown(),
+        };
+
+        let text = TableBuilder::collapse_whitespace(text);
+        for (separator, text) in [("<table>", Some(&text)), ("<caption>", table.caption.as_ref())] {
+            if let Some(text) = text.filter(|text| !text.is_empty()) {
+                context.output.add_break(separator);
+                text.chars().for_each(|ch| context.write(ch));
+            }
+        }
+
+        context.output.add_break("<table>");
+        context.output.start_preformatted(false);
+        rendered.chars().for_each(|ch| context.write(ch));
+        context.output.end_preformatted();
+        context.output.add_break("</table>");
+    }
+}
+
+impl ElementHandler for TableHandler {
+    fn on_start_element(&mut self, element: &Element, context: &mut HandlerContext) -> Option<bool> {
+        if element.name == "table" {
+            self.tables.push(TableBuilder { enabled: context.output_enabled, ..Default::default() });
+            return None;
+        }
+
+        let table = self.tables.last_mut()?;
+        match element.name.as_str() {
+            "caption" => table.caption = Some(String::new()),
+            "thead" | "tbody" | "tfoot" => {
+                table.finish_row_group();
+                table.header_group = element.name == "thead";
+            }
+            // Rows and cells are closed by the next ones if their close tags are missing
+            "tr" => {
+                table.finish_row();
+                table.row = Some(Row { header: table.header_group, ..Default::default() });
+            }
+            "td" | "th" => {
+                table.finish_cell();
+                table.cell = Some(Cell {
+                    text: String::new(),
+                    colspan: TableBuilder::span(element, "colspan", 1000),
+                    rowspan: TableBuilder::span(element, "rowspan", 65534),
+                    header: element.name == "th",
+                });
+            }
+            "br" => table.write(' '),
+            _ => {}
+        }
+        None
+    }
+
+    fn on_end_element(&mut self, element: &Element, context: &mut HandlerContext) {
+        if element.name == "table" {
+            let Some(mut builder) = self.tables.pop() else {
+                return;
+            };
+            builder.finish_row_group();
+            let table = builder.table;
+            if !builder.enabled {
+                return;
+            }
+
+            // Nested tables are part of the text of the cells containing them
+            match self.tables.last_mut() {
+                Some(parent) => parent.write_str(&table.to_text()),
+                None => TableHandler::render(&table, &builder.text, context),
+            }
+            if context.parameters.tables {
+                context.output.add_table(table);
+            }
+            return;
+        }
+
+        let Some(table) = self.tables.last_mut() else {
+            return;
+        };
+        match element.name.as_str() {
+            "caption" => {
+                if let Some(caption) = table.caption.take() {
+                    let caption = TableBuilder::collapse_whitespace(&caption);
+                    table.table.caption = (!caption.is_empty()).then_some(caption);
+                }
+            }
+            "thead" | "tbody" | "tfoot" => table.finish_row_group(),
+            "tr" => table.finish_row(),
+            "td" | "th" => table.finish_cell(),
+            _ => {}
+        }
+    }
+
+    fn on_text(&mut self, ch: char, context: &mut HandlerContext) -> bool {
+        let Some(table) = self.tables.last_mut() else {
+            return false;
+        };
+        if context.output_enabled {
+            table.write(ch);
+        }
+        TableHandler::renders_tables(context)
+    }
+}
+
+impl TableBuilder {
+    fn span(element: &Element, name: &str, max: usize) -> usize {
+        element.attribute(name).and_then(|value| value.trim().parse().ok()).unwrap_or(1).clamp(1, max)
+    }
+
+    fn collapse_whitespace(text: &str) -> String {
+        text.split_whitespace().collect::<Vec<&str>>().join(" ")
+    }
+
+    fn write(&mut self, ch: char) {
+        if let Some(caption) = self.caption.as_mut() {
+            caption.push(ch);
+        } else if let Some(cell) = self.cell.as_mut() {
+            cell.text.push(ch);
+        } else {
+            self.text.push(ch);
+        }
+    }
+
+    fn write_str(&mut self, text: &str) {
+        self.write(' ');
+        text.chars().for_each(|ch| self.write(ch));
+        self.write(' ');
+    }
+
+    // Add the cells of the previous rows spanning into the next columns of the row
+    fn fill_row_spans(&self, row: &mut Row) {
+        while let Some(span) = self.row_spans.iter().find(|span| span.column == row.cells.len()) {
+            row.cells.push(span.text.clone());
+        }
+    }
+
+    fn finish_cell(&mut self) {
+        let Some(cell) = self.cell.take() else {
+            return;
+        };
+        // Cells outside of rows start a new row
+        let mut row = self.row.take().unwrap_or_else(|| Row { header: self.header_group, ..Default::default() });
+        self.fill_row_spans(&mut row);
+
+        let text = TableBuilder::collapse_whitespace(&cell.text);
+        if cell.rowspan > 1 {
+            for column in row.cells.len()..row.cells.len() + cell.colspan {
+                row.row_spans.push(RowSpan { column, rows: cell.rowspan - 1, text: text.clone() });
+            }
+        }
+        row.cells.extend(std::iter::repeat_n(text, cell.colspan));
+        row.data_cells |= !cell.header;
+        self.row = Some(row);
+    }
+
+    fn finish_row(&mut self) {
+        self.finish_cell();
+        let Some(mut row) = self.row.take() else {
+            return;
+        };
+
+        // Cells spanning into the columns after the last cell of the row
+        let mut spans: Vec<&RowSpan> = self.row_spans.iter().filter(|span| span.column >= row.cells.len()).collect();
+        spans.sort_by_key(|span| span.column);
+        for span in spans {
+            row.cells.resize(span.column, String::new());
+            row.cells.push(span.text.clone());
+        }
+
+        // Spanning cells of the previous rows are used by one less row, while the cells of this row are added
+        self.row_spans.iter_mut().for_each(|span| span.rows -= 1);
+        self.row_spans.retain(|span| span.rows > 0);
+        self.row_spans.append(&mut row.row_spans);
+
+        if row.cells.is_empty() {
+            return;
+        }
+        // Rows of the thead element and rows containing header cells only before the first body row are header rows
+        if (row.header || !row.data_cells) && self.table.rows.is_empty() {
+            self.table.header.push(row.cells);
+        } else {
+            self.table.rows.push(row.cells);
+        }
+    }
+
+    // Cells do not span into the next row group
+    fn finish_row_group(&mut self) {
+        self.finish_row();
+        self.row_spans.clear();
+        self.header_group = false;
+    }
+}
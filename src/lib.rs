@@ -0,0 +1,6 @@
+pub mod body_text_extractor;
+pub mod error;
+pub mod html;
+pub mod text_extractor;
+pub mod util;
+pub mod xml;
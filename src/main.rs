@@ -1,16 +1,31 @@
-mod body_text_extractor;
-pub mod error;
-mod html;
-mod text_extractor;
-mod util;
+use std::{collections::HashMap, path::Path, sync::Arc};
 
-use axum::{body::Body, extract::Query, http::StatusCode, response::IntoResponse, routing::post, Router};
-use body_text_extractor::BodyTextExtractor;
-use text_extractor::ExtractParameters;
+use axum::{body::Body, extract::{Query, State}, http::{header::{CONTENT_LANGUAGE, CONTENT_TYPE}, HeaderMap, StatusCode}, response::IntoResponse, routing::post, Json, Router};
+use serde::Deserialize;
+use textminer::{
+    body_text_extractor::BodyTextExtractor,
+    html::{html_text_extractor::HtmlTextExtractor, profile::Profile},
+    text_extractor::{default_shingle_size, ExtractParameters},
+    util::{fingerprint::Fingerprint, json_writer::JsonWriter}
+};
 
 #[tokio::main]
 async fn main() {
-    let app = Router::new().route("/extract", post(extract));
+    // Extraction rules are loaded from the directory set by the TEXTMINER_PROFILES environment variable (or 'profiles' by default)
+    let directory = std::env::var("TEXTMINER_PROFILES").unwrap_or(String::from("profiles"));
+    let profiles = if Path::new(&directory).is_dir() {
+        match Profile::load_all(Path::new(&directory)) {
+            Ok(profiles) => profiles,
+            Err(error) => {
+                eprintln!("Could not load profiles from {}: {}", directory, error.message);
+                std::process::exit(1);
+            }
+        }
+    } else {
+        HashMap::new()
+    };
+
+    let app = Router::new().route("/extract", post(extract)).route("/compare", post(compare)).with_state(Arc::new(profiles));
     let listener = tokio::net::TcpListener::bind("localhost:8080")
         .await
         .unwrap();
@@ -18,7 +33,102 @@ async fn main() {
     axum::serve(listener, app).await.unwrap();
 }
 
-async fn extract(parameters: Query<ExtractParameters>, request_body: Body) -> impl IntoResponse {
+async fn extract(State(profiles): State<Arc<HashMap<String, Profile>>>, parameters: Query<ExtractParameters>, headers: HeaderMap, request_body: Body) -> impl IntoResponse {
+    let profile = match &parameters.profile {
+        Some(name) => match profiles.get(name) {
+            Some(profile) => Some(profile.clone()),
+            None => return (StatusCode::BAD_REQUEST, error_body(&format!("Unknown profile: {}", name))),
+        },
+        None => None,
+    };
+
+    // The first language of the Content-Language header is the declared language of the document
+    let mut parameters = parameters.0;
+    parameters.content_language = headers
+        .get(CONTENT_LANGUAGE)
+        .and_then(|value| value.to_str().ok())
+        .and_then(|value| value.split(',').next())
+        .map(|language| String::from(language.trim()))
+        .filter(|language| !language.is_empty());
+    // Format of the body is detected from the Content-Type header unless it is set by the input_format parameter
+    parameters.content_type = headers.get(CONTENT_TYPE).and_then(|value| value.to_str().ok()).map(String::from);
+
     // The status code 202 (ACCEPTED) indicates that HTTP header is sent before processing is finished, therefore status is not known
-    (StatusCode::ACCEPTED, BodyTextExtractor::extract(request_body, parameters.output_format).await)
+    (StatusCode::ACCEPTED, BodyTextExtractor::extract(request_body, parameters, profile).await)
+}
+
+// Request comparing two documents (HTML) or fingerprints returned by the extract endpoint
+#[derive(Deserialize)]
+struct CompareRequest {
+    first: Comparable,
+    second: Comparable,
+    // Shingle size used to fingerprint the documents
+    #[serde(default = "default_shingle_size")]
+    shingle_size: usize,
+}
+
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum Comparable {
+    Fingerprint(Fingerprint),
+    Document(String),
+}
+
+async fn compare(request: Json<CompareRequest>) -> impl IntoResponse {
+    let request = request.0;
+    let result = match (fingerprint(request.first, request.shingle_size).await, fingerprint(request.second, request.shingle_size).await) {
+        (Ok(first), Ok(second)) => first.compare(&second),
+        (Err(error), _) | (_, Err(error)) => Err(error),
+    };
+    match result {
+        Ok(similarity) => (StatusCode::OK, Body::from(JsonWriter::to_json(&similarity))),
+        Err(message) => (StatusCode::BAD_REQUEST, error_body(&message)),
+    }
+}
+
+// Documents are fingerprinted by extracting their text with the default parameters
+async fn fingerprint(comparable: Comparable, shingle_size: usize) -> Result<Fingerprint, String> {
+    let document = match comparable {
+        Comparable::Fingerprint(fingerprint) => return Ok(fingerprint),
+        Comparable::Document(document) => document,
+    };
+    let parameters = ExtractParameters { shingle_size, ..Default::default() };
+    HtmlTextExtractor::new().fingerprint(&mut document.as_bytes(), &parameters).await.map_err(|error| error.message)
+}
+
+// Body of responses for requests rejected before processing
+fn error_body(message: &str) -> Body {
+    Body::from(format!("{{\n \"error\": {}\n}}", JsonWriter::to_json(message)))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{compare, CompareRequest};
+    use axum::{body::to_bytes, http::StatusCode, response::IntoResponse, Json};
+
+    async fn compare_json(request: serde_json::Value) -> (StatusCode, serde_json::Value) {
+        let request: CompareRequest = serde_json::from_value(request).unwrap();
+        let response = compare(Json(request)).await.into_response();
+        let status = response.status();
+        let response_bytes = to_bytes(response.into_body(), usize::MAX).await.unwrap();
+        (status, serde_json::from_slice(&response_bytes).unwrap())
+    }
+
+    #[tokio::test]
+    async fn test_compare() {
+        let document = "<html><body><p>The city council announced on Monday that the new bridge over the river will open to traffic \
+            at the end of the year.</p></body></html>";
+        let (status, output) = compare_json(serde_json::json!({ "first": document, "second": document })).await;
+        assert_eq!(status, StatusCode::OK);
+        assert_eq!(output, serde_json::json!({ "simhash_distance": 0, "simhash_similarity": 1.0, "minhash_similarity": 1.0 }));
+
+        // Fingerprints returned by the extract endpoint are compared to the documents fingerprinted with the same shingle size
+        let fingerprint = serde_json::json!({ "shingle_size": 3, "simhash": "0000000000000000", "minhash": vec![0; 64] });
+        let (status, output) = compare_json(serde_json::json!({ "first": fingerprint, "second": document })).await;
+        assert_eq!(status, StatusCode::OK);
+        assert_eq!(output["minhash_similarity"], 0.0);
+        let (status, output) = compare_json(serde_json::json!({ "first": fingerprint, "second": document, "shingle_size": 2 })).await;
+        assert_eq!(status, StatusCode::BAD_REQUEST);
+        assert_eq!(output["error"], "Fingerprints must have the same shingle size (3 and 2)");
+    }
 }
@@ -1,21 +1,248 @@
 use crate::error::Error;
-use serde::Deserialize;
+use serde::{Deserialize, Serialize};
 use tokio::io::{AsyncRead, AsyncWrite};
 
-#[derive(Deserialize, PartialEq, Clone, Copy)]
-pub enum OutputFormat { Simple, Advanced }
+// Simple format contains the text in one result, Advanced contains a result for each text block, Sections
+// contains the text blocks nested under the headings of the document and Chunks contains parts of the text of
+// about the same size
+#[derive(Deserialize, PartialEq, Clone, Copy, Default)]
+pub enum OutputFormat { #[default] Simple, Advanced, Sections, Chunks }
 
-#[derive(Deserialize)]
+// Unit of the chunk size (tokens are approximated as four characters of words)
+#[derive(Deserialize, PartialEq, Clone, Copy, Default)]
+pub enum ChunkUnit {
+    #[default]
+    #[serde(alias = "characters")]
+    Characters,
+    #[serde(alias = "words")]
+    Words,
+    #[serde(alias = "tokens")]
+    Tokens
+}
+
+// Full mode extracts all text of the document, main mode only the main content (without navigation, footers, etc.)
+#[derive(Deserialize, PartialEq, Clone, Copy, Default)]
+pub enum ContentMode {
+    #[default]
+    #[serde(alias = "full")]
+    Full,
+    #[serde(alias = "main")]
+    Main
+}
+
+// Advanced format contains a result for each text block or for each sentence of the text blocks
+#[derive(Deserialize, PartialEq, Clone, Copy, Default)]
+pub enum Granularity {
+    #[default]
+    #[serde(alias = "block")]
+    Block,
+    #[serde(alias = "sentence")]
+    Sentence
+}
+
+// Text mode writes the text of table cells in separate lines, TSV and Markdown modes render each table as one text block
+#[derive(Deserialize, PartialEq, Clone, Copy, Default)]
+pub enum TableFormat {
+    #[default]
+    #[serde(alias = "text")]
+    Text,
+    #[serde(alias = "tsv")]
+    Tsv,
+    #[serde(alias = "markdown")]
+    Markdown
+}
+
+// Format of the request body. Only HTML, XML and RSS or Atom feeds can be extracted, the other formats are detected to
+// report them as unsupported.
+#[derive(Deserialize, Serialize, PartialEq, Clone, Copy, Debug)]
+#[serde(rename_all(serialize = "lowercase"))]
+pub enum InputFormat {
+    #[serde(alias = "html")]
+    Html,
+    #[serde(alias = "xml")]
+    Xml,
+    #[serde(alias = "feed")]
+    Feed,
+    #[serde(alias = "pdf")]
+    Pdf,
+    #[serde(alias = "zip")]
+    Zip,
+    #[serde(alias = "gzip")]
+    Gzip
+}
+
+// Unicode normalization form of the extracted text
+#[derive(Deserialize, PartialEq, Clone, Copy, Default)]
+pub enum Normalization {
+    #[default]
+    #[serde(alias = "none")]
+    None,
+    #[serde(alias = "nfc")]
+    Nfc,
+    #[serde(alias = "nfkc")]
+    Nfkc
+}
+
+#[derive(Deserialize, Clone)]
 pub struct ExtractParameters {
-    pub output_format: OutputFormat
+    pub output_format: OutputFormat,
+    // Format of the request body (detected from the Content-Type header and the content if not specified). The chosen
+    // format is reported in the output.
+    #[serde(default)]
+    pub input_format: Option<InputFormat>,
+    #[serde(default)]
+    pub content_mode: ContentMode,
+    #[serde(default)]
+    pub granularity: Granularity,
+    // Skip elements with the 'hidden' attribute, 'display:none' or 'visibility:hidden' inline styles and template elements
+    #[serde(default = "default_true")]
+    pub skip_hidden: bool,
+    // Skip elements marked with aria-hidden="true"
+    #[serde(default = "default_true")]
+    pub skip_aria_hidden: bool,
+    // Skip the fallback content of noscript elements
+    #[serde(default)]
+    pub skip_noscript: bool,
+    // CSS selectors (or names of XML elements) to extract content from (content of the whole body is extracted if not
+    // specified)
+    #[serde(default)]
+    pub include: Option<String>,
+    // CSS selectors (or names of XML elements) of elements to exclude from the output
+    #[serde(default)]
+    pub exclude: Option<String>,
+    // Name of the profile containing the extraction rules of a site
+    #[serde(default)]
+    pub profile: Option<String>,
+    // Return the tables of the document as rows of cells in the 'tables' field of the output
+    #[serde(default)]
+    pub tables: bool,
+    // Format of the tables in the extracted text
+    #[serde(default)]
+    pub table_format: TableFormat,
+    // Write bullets and numbers before list items and indent nested lists
+    #[serde(default)]
+    pub list_markers: bool,
+    // Bullets of unordered lists for each nesting level (the last one is used for the deeper levels)
+    #[serde(default = "default_bullets")]
+    pub bullets: String,
+    // Add the path of the elements containing each text block and the text of the heading before it to the Advanced output
+    #[serde(default)]
+    pub paths: bool,
+    // Target size of the chunks in the Chunks format and the size of the text repeated from the previous chunk
+    #[serde(default = "default_chunk_size")]
+    pub chunk_size: usize,
+    #[serde(default)]
+    pub chunk_unit: ChunkUnit,
+    #[serde(default)]
+    pub chunk_overlap: usize,
+    // Report the declared and the detected language of the document (and of each text block in the Advanced output)
+    #[serde(default)]
+    pub detect_language: bool,
+    // Report the counts of characters, words and sentences and the readability of the document (and of each text block in
+    // the Advanced output). Flesch scores are reported for all languages, and a readability score of the language for
+    // German, French, Spanish, Italian, Dutch, Portuguese and Russian texts.
+    #[serde(default)]
+    pub stats: bool,
+    // Number of keywords and keyphrases reported for the document
+    #[serde(default)]
+    pub keywords: usize,
+    // Number of the most representative sentences of the main content reported as the summary of the document
+    #[serde(default)]
+    pub summary: usize,
+    // Report the SimHash and MinHash fingerprints of the text with shingles of the given number of words
+    #[serde(default)]
+    pub fingerprints: bool,
+    #[serde(default = "default_shingle_size")]
+    pub shingle_size: usize,
+    // Replace personal data (like e-mail addresses and phone numbers) with placeholders and report their counts
+    #[serde(default)]
+    pub redact: bool,
+    // Report the URLs, e-mail addresses, dates, amounts of money, percentages and phone numbers found in the text
+    #[serde(default)]
+    pub entities: bool,
+    // Normalize the text to NFC or NFKC form
+    #[serde(default)]
+    pub normalization: Normalization,
+    // Remove invisible formatting characters (like soft hyphens and zero width joiners) and replace non-breaking spaces
+    #[serde(default)]
+    pub remove_invisible: bool,
+    // Replace typographic quotes and dashes with ASCII ones
+    #[serde(default)]
+    pub fold_punctuation: bool,
+    // Replace ligatures (like 'ﬁ') with the letters they consist of
+    #[serde(default)]
+    pub expand_ligatures: bool,
+    // Language of the request body declared by the Content-Language header
+    #[serde(skip)]
+    pub content_language: Option<String>,
+    // Media type of the request body declared by the Content-Type header
+    #[serde(skip)]
+    pub content_type: Option<String>
+}
+
+impl Default for ExtractParameters {
+    fn default() -> Self {
+        Self {
+            output_format: OutputFormat::Simple,
+            input_format: None,
+            content_mode: ContentMode::Full,
+            granularity: Granularity::Block,
+            skip_hidden: true,
+            skip_aria_hidden: true,
+            skip_noscript: false,
+            include: None,
+            exclude: None,
+            profile: None,
+            tables: false,
+            table_format: TableFormat::Text,
+            list_markers: false,
+            bullets: default_bullets(),
+            paths: false,
+            chunk_size: default_chunk_size(),
+            chunk_unit: ChunkUnit::Characters,
+            chunk_overlap: 0,
+            detect_language: false,
+            stats: false,
+            keywords: 0,
+            summary: 0,
+            fingerprints: false,
+            redact: false,
+            entities: false,
+            shingle_size: default_shingle_size(),
+            normalization: Normalization::None,
+            remove_invisible: false,
+            fold_punctuation: false,
+            expand_ligatures: false,
+            content_language: None,
+            content_type: None
+        }
+    }
+}
+
+fn default_true() -> bool {
+    true
+}
+
+fn default_chunk_size() -> usize {
+    1000
+}
+
+pub fn default_shingle_size() -> usize {
+    3
+}
+
+fn default_bullets() -> String {
+    String::from("•◦▪")
 }
 
+// Futures of the extractors are spawned by the server, therefore implementations must keep them Send
+#[allow(async_fn_in_trait)]
 pub trait TextExtractor {
     async fn extract<R: AsyncRead + Unpin + Send, W: AsyncWrite + Unpin + Send>(
         &mut self,
         reader: &mut R,
         writer: &mut W,
-        output_format: OutputFormat
+        parameters: &ExtractParameters
     ) -> Result<(), Error>
     where
         Self: Send;
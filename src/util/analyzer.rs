@@ -0,0 +1,57 @@
+use std::any::Any;
+
+use super::{entities::EntityExtractor, fingerprint::FingerprintBuilder, fragment::Fragment, keywords::KeywordExtractor, stats::TextCounts, summarizer::Summarizer};
+use crate::text_extractor::{ContentMode, ExtractParameters};
+
+// An analysis of the whole document (like its keywords or its summary). It is fed with the completed fragments written
+// to the output, and its result is written as a field of the document after the results.
+pub trait Analyzer: Any + Send {
+    fn add(&mut self, fragment: &Fragment);
+
+    // Write the field with the result to the output. The language is the declared language of the document, or the
+    // detected one if it is not declared.
+    fn write_result(&mut self, parameters: &ExtractParameters, language: Option<&str>, output: &mut String);
+
+    // Whether the language of the document is detected for the result if it is not declared
+    fn needs_language(&self) -> bool {
+        false
+    }
+}
+
+// Create the analyzers of the document requested by the parameters (their results are written in this order)
+pub fn analyzers(parameters: &ExtractParameters) -> Vec<Box<dyn Analyzer>> {
+    let mut analyzers: Vec<Box<dyn Analyzer>> = Vec::new();
+    if parameters.stats {
+        analyzers.push(Box::new(TextCounts::default()));
+    }
+    if parameters.keywords > 0 {
+        analyzers.push(Box::new(KeywordExtractor::default()));
+    }
+    if parameters.summary > 0 {
+        analyzers.push(Box::new(Summarizer::new(parameters.content_mode == ContentMode::Full)));
+    }
+    if parameters.entities {
+        analyzers.push(Box::new(EntityExtractor::default()));
+    }
+    if parameters.fingerprints {
+        analyzers.push(Box::new(FingerprintBuilder::new(parameters.shingle_size)));
+    }
+    analyzers
+}
+
+// Collect the text of the fragments (separated by new line characters), so it can be taken without reading the serialized JSON
+#[derive(Default)]
+pub struct TextCollector {
+    pub text: String,
+}
+
+impl Analyzer for TextCollector {
+    fn add(&mut self, fragment: &Fragment) {
+        if !self.text.is_empty() {
+            self.text.push('\n');
+        }
+        self.text.push_str(&fragment.text);
+    }
+
+    fn write_result(&mut self, _parameters: &ExtractParameters, _language: Option<&str>, _output: &mut String) {}
+}
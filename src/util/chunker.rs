@@ -0,0 +1,218 @@
+use serde::Serialize;
+
+use super::{fragment::Fragment, sentences::SentenceSplitter};
+use crate::text_extractor::ChunkUnit;
+
+// A part of the document text of about the requested size
+#[derive(Serialize)]
+pub struct Chunk {
+    pub text: String,
+    // Character offsets of the chunk in the text of the Simple format (text blocks separated by new line characters)
+    pub start: usize,
+    pub end: usize,
+    // Size of the chunk in the requested unit
+    pub size: usize,
+    // Headings of the sections containing the beginning of the chunk (from the top level)
+    pub headings: Vec<String>,
+}
+
+// A sentence (or a part of a long sentence) which is not split into chunks
+struct Unit {
+    start: usize,
+    end: usize,
+    size: usize,
+    // Whether the unit starts a text block or a heading (chunks are preferred to start at these boundaries)
+    block_start: bool,
+    heading_start: bool,
+    headings: Vec<String>,
+}
+
+// Split the text blocks of a document into chunks of a target size, which break at heading, block or sentence boundaries
+pub struct Chunker {
+    unit: ChunkUnit,
+    target_size: usize,
+    overlap: usize,
+}
+
+impl Chunker {
+    pub fn new(unit: ChunkUnit, target_size: usize, overlap: usize) -> Self {
+        Self { unit, target_size: target_size.max(1), overlap: overlap.min(target_size / 2) }
+    }
+
+    pub fn split(&self, fragments: &[Fragment]) -> Vec<Chunk> {
+        let text: Vec<char> = fragments.iter().map(|fragment| fragment.text.as_str()).collect::<Vec<&str>>().join("\n").chars().collect();
+        let units = self.units(fragments);
+
+        let mut chunks = Vec::new();
+        let (mut first, mut previous_end) = (0, 0);
+        while first < units.len() {
+            let last = self.chunk_end(&units, first, previous_end);
+            let (start, end) = (units[first].start, units[last - 1].end);
+            let chunk_text: String = text[start..end].iter().collect();
+            chunks.push(Chunk { size: self.size(&chunk_text), text: chunk_text, start, end, headings: units[first].headings.clone() });
+            if last == units.len() {
+                break;
+            }
+
+            // The next chunk repeats the last units of the chunk up to the size of the overlap, unless the next unit does
+            // not fit into the chunk together with them
+            let mut next = last;
+            while next > first + 1 && units[next - 1..last].iter().map(|unit| unit.size).sum::<usize>() <= self.overlap {
+                next -= 1;
+            }
+            if units[next..=last].iter().map(|unit| unit.size).sum::<usize>() > self.target_size {
+                next = last;
+            }
+            (first, previous_end) = (next, last);
+        }
+        chunks
+    }
+
+    // Find the end of the chunk starting at a unit. Chunks break before headings if they are at least half full and
+    // at the start of text blocks (but not between a heading and its text), otherwise at the last sentence fitting
+    // into the chunk. A chunk starting with the overlap of the previous one ends after the end of the previous chunk.
+    fn chunk_end(&self, units: &[Unit], first: usize, previous_end: usize) -> usize {
+        let half_size = self.target_size.div_ceil(2);
+        let mut size = units[first].size;
+        let mut last = first + 1;
+        let mut block_end = None;
+        while last < units.len() && size + units[last].size <= self.target_size {
+            if last > previous_end && units[last].heading_start && size >= half_size {
+                return last;
+            }
+            if last > previous_end && (units[last].heading_start || (units[last].block_start && !units[last - 1].heading_start && size >= half_size)) {
+                block_end = Some(last);
+            }
+            size += units[last].size;
+            last += 1;
+        }
+        match block_end {
+            Some(block_end) if last < units.len() && !units[last].block_start => block_end,
+            _ => last,
+        }
+    }
+
+    // Split the fragments into sentences and split the sentences longer than the chunk size at word boundaries
+    fn units(&self, fragments: &[Fragment]) -> Vec<Unit> {
+        let mut units = Vec::new();
+        let mut headings: Vec<(u8, String)> = Vec::new();
+        let mut offset = 0;
+        for fragment in fragments {
+            if let Some(level) = fragment.heading_level {
+                headings.retain(|(heading_level, _)| *heading_level < level);
+                headings.push((level, fragment.text.clone()));
+            }
+            let heading_trail: Vec<String> = headings.iter().map(|(_, heading)| heading.clone()).collect();
+
+            let chars: Vec<char> = fragment.text.chars().collect();
+            let mut block_start = true;
+            for (start, end) in SentenceSplitter::split(&fragment.text) {
+                for (piece_start, piece_end) in self.pieces(&chars, start, end) {
+                    let piece: String = chars[piece_start..piece_end].iter().collect();
+                    units.push(Unit {
+                        start: offset + piece_start,
+                        end: offset + piece_end,
+                        size: self.size(&piece),
+                        block_start,
+                        heading_start: block_start && fragment.heading_level.is_some(),
+                        headings: heading_trail.clone(),
+                    });
+                    block_start = false;
+                }
+            }
+            offset += chars.len() + 1;
+        }
+        units
+    }
+
+    // Split a sentence at word boundaries into pieces not longer than the chunk size
+    fn pieces(&self, chars: &[char], start: usize, end: usize) -> Vec<(usize, usize)> {
+        let sentence: String = chars[start..end].iter().collect();
+        if self.size(&sentence) <= self.target_size {
+            return vec![(start, end)];
+        }
+
+        let mut pieces = Vec::new();
+        let (mut piece_start, mut size) = (start, 0);
+        let mut position = start;
+        while position < end {
+            let word_start = position;
+            while position < end && !chars[position].is_whitespace() {
+                position += 1;
+            }
+            let word: String = chars[word_start..position].iter().collect();
+            let word_size = self.size(&word);
+            if size > 0 && size + word_size > self.target_size {
+                pieces.push((piece_start, chars[..word_start].iter().rposition(|ch| !ch.is_whitespace()).map_or(word_start, |last| last + 1)));
+                piece_start = word_start;
+                size = 0;
+            }
+            size += word_size;
+            while position < end && chars[position].is_whitespace() {
+                position += 1;
+            }
+        }
+        pieces.push((piece_start, end));
+        pieces
+    }
+
+    // Size of a text in characters, words or approximate tokens (about four characters each)
+    fn size(&self, text: &str) -> usize {
+        match self.unit {
+            ChunkUnit::Characters => text.chars().count(),
+            ChunkUnit::Words => text.split_whitespace().count(),
+            ChunkUnit::Tokens => text.split_whitespace().map(|word| word.chars().count().div_ceil(4)).sum(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn fragments() -> Vec<Fragment> {
+        let fragment = |text: &str, heading_level: Option<u8>| Fragment { text: String::from(text), heading_level, ..Default::default() };
+        vec![
+            fragment("Guide", Some(1)),
+            fragment("First sentence here. Second sentence here.", None),
+            fragment("Setup", Some(2)),
+            fragment("Install it. Run it now.", None),
+        ]
+    }
+
+    fn texts(chunks: &[Chunk]) -> Vec<&str> {
+        chunks.iter().map(|chunk| chunk.text.as_str()).collect()
+    }
+
+    #[test]
+    fn test_chunks() {
+        let chunks = Chunker::new(ChunkUnit::Words, 8, 0).split(&fragments());
+        let chunks: Vec<(&str, usize, usize, usize, &[String])> =
+            chunks.iter().map(|chunk| (chunk.text.as_str(), chunk.start, chunk.end, chunk.size, chunk.headings.as_slice())).collect();
+        assert_eq!(chunks, vec![
+            ("Guide\nFirst sentence here. Second sentence here.", 0, 48, 7, &[String::from("Guide")][..]),
+            ("Setup\nInstall it. Run it now.", 49, 78, 6, &[String::from("Guide"), String::from("Setup")][..])
+        ]);
+
+        // Sentences longer than the chunk size are split at word boundaries
+        let chunks = Chunker::new(ChunkUnit::Characters, 12, 0).split(&fragments()[1..2]);
+        assert_eq!(texts(&chunks), vec!["First", "sentence", "here. Second", "sentence", "here."]);
+        let chunks = Chunker::new(ChunkUnit::Tokens, 4, 0).split(&fragments()[1..2]);
+        assert_eq!(texts(&chunks), vec!["First sentence", "here.", "Second sentence", "here."]);
+    }
+
+    #[test]
+    fn test_chunk_overlap() {
+        // Overlapping chunks repeat the last sentence of the previous chunk and continue with new text
+        let chunks = Chunker::new(ChunkUnit::Words, 8, 3).split(&fragments());
+        assert_eq!(texts(&chunks), vec![
+            "Guide\nFirst sentence here. Second sentence here.",
+            "Second sentence here.\nSetup\nInstall it.",
+            "Setup\nInstall it. Run it now."
+        ]);
+
+        // Overlap is limited to the half of the chunk size
+        let chunker = Chunker::new(ChunkUnit::Words, 8, 6);
+        assert_eq!(chunker.overlap, 4);
+    }
+}
@@ -0,0 +1,254 @@
+use regex::{Captures, Regex};
+use serde::Serialize;
+use std::sync::OnceLock;
+
+use super::{analyzer::Analyzer, fragment::Fragment, json_writer::JsonWriter, redactor::{Redactor, EMAIL_PATTERN, PHONE_PATTERN}};
+use crate::text_extractor::ExtractParameters;
+
+// Names of the months in English (with abbreviations), German and French, used to read dates written with words
+const MONTH_NAMES: [(&str, u32); 38] = [
+    ("january", 1), ("february", 2), ("march", 3), ("april", 4), ("may", 5), ("june", 6), ("july", 7), ("august", 8),
+    ("september", 9), ("october", 10), ("november", 11), ("december", 12),
+    ("jan", 1), ("feb", 2), ("mar", 3), ("apr", 4), ("jun", 6), ("jul", 7), ("aug", 8), ("sep", 9), ("sept", 9), ("oct", 10),
+    ("nov", 11), ("dec", 12),
+    ("januar", 1), ("jänner", 1), ("februar", 2), ("märz", 3), ("mai", 5), ("juni", 6), ("juli", 7), ("oktober", 10),
+    ("dezember", 12), ("avril", 4), ("mars", 3), ("juin", 6), ("juillet", 7), ("août", 8),
+];
+
+// Currencies written with symbols or codes
+const CURRENCIES: [(&str, &str); 12] = [
+    ("$", "USD"), ("€", "EUR"), ("£", "GBP"), ("¥", "JPY"), ("Ft", "HUF"), ("zł", "PLN"),
+    ("USD", "USD"), ("EUR", "EUR"), ("GBP", "GBP"), ("CHF", "CHF"), ("HUF", "HUF"), ("JPY", "JPY"),
+];
+
+const AMOUNT_PATTERN: &str = r"\d{1,3}(?:[,. ]\d{3})+(?:[.,]\d{1,2})?|\d+(?:[.,]\d{1,2})?";
+
+#[derive(Clone, Copy, PartialEq)]
+enum Pattern { Url, Email, IsoDate, DottedDate, YearFirstDottedDate, SlashDate, DayMonthDate, MonthDayDate, MoneyBefore, MoneyAfter, Percentage, Phone }
+
+// A mention found in the text with its character offsets in the text of the Simple format (the text blocks separated by
+// new line characters)
+#[derive(Serialize)]
+pub struct Entity {
+    #[serde(rename = "type")]
+    pub kind: &'static str,
+    pub text: String,
+    pub start: usize,
+    pub end: usize,
+    // Index of the text block containing the mention (the index of its result in the Advanced format with block
+    // granularity) and the character offsets of the mention in the text of the block
+    pub block: usize,
+    pub block_start: usize,
+    pub block_end: usize,
+    // Normalized value (ISO 8601 dates, URLs and e-mail addresses)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub value: Option<String>,
+    // Amounts of money and percentages
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub amount: Option<f64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub currency: Option<&'static str>,
+}
+
+// Find URLs, e-mail addresses, dates, amounts of money, percentages and phone numbers in the text of the fragments
+#[derive(Default)]
+pub struct EntityExtractor {
+    pub entities: Vec<Entity>,
+    // Number of the text blocks and the length of the text added so far (blocks are separated by new line characters)
+    blocks: usize,
+    length: Option<usize>,
+}
+
+impl EntityExtractor {
+    pub fn add(&mut self, text: &str) {
+        let offset = self.length.map_or(0, |length| length + 1);
+        let mut entities: Vec<Entity> = Vec::new();
+        for (pattern, regex) in EntityExtractor::patterns() {
+            for captures in regex.captures_iter(text) {
+                let found = captures.get(0).expect("Captures contain the whole match.");
+                let (start, end) = (text[..found.start()].chars().count(), text[..found.end()].chars().count());
+                if entities.iter().any(|entity| start < entity.end && end > entity.start) {
+                    continue;
+                }
+                if let Some(mut entity) = EntityExtractor::entity(*pattern, &captures) {
+                    (entity.start, entity.end) = (start, end);
+                    entities.push(entity);
+                }
+            }
+        }
+        entities.sort_by_key(|entity| entity.start);
+        let block = self.blocks;
+        self.entities.extend(entities.into_iter().map(|entity| Entity {
+            start: entity.start + offset,
+            end: entity.end + offset,
+            block,
+            block_start: entity.start,
+            block_end: entity.end,
+            ..entity
+        }));
+        self.blocks += 1;
+        self.length = Some(offset + text.chars().count());
+    }
+
+    fn entity(pattern: Pattern, captures: &Captures) -> Option<Entity> {
+        let text = &captures[0];
+        let group = |name: &str| captures.name(name).map_or("", |found| found.as_str());
+        let number = |name: &str| group(name).parse::<u32>().ok();
+        let entity = |kind: &'static str| Entity {
+            kind,
+            text: String::from(text),
+            start: 0,
+            end: 0,
+            block: 0,
+            block_start: 0,
+            block_end: 0,
+            value: None,
+            amount: None,
+            currency: None,
+        };
+
+        match pattern {
+            Pattern::Url => {
+                let url = if text.starts_with("www.") { format!("http://{}", text) } else { String::from(text) };
+                Some(Entity { value: Some(url), ..entity("url") })
+            }
+            Pattern::Email => Some(Entity { value: Some(text.to_lowercase()), ..entity("email") }),
+            Pattern::Phone => Redactor::is_phone_number(text).then(|| entity("phone")),
+            Pattern::IsoDate | Pattern::DottedDate | Pattern::YearFirstDottedDate | Pattern::DayMonthDate | Pattern::MonthDayDate => {
+                let month = number("month").or_else(|| EntityExtractor::month(group("month_name")))?;
+                let date = EntityExtractor::date(number("year")?, month, number("day")?)?;
+                Some(Entity { value: Some(date), ..entity("date") })
+            }
+            // Dates with slashes are read as month/day/year unless the first number can only be a day
+            Pattern::SlashDate => {
+                let (first, second) = (number("first")?, number("second")?);
+                let (month, day) = if first > 12 { (second, first) } else { (first, second) };
+                let date = EntityExtractor::date(number("year")?, month, day)?;
+                Some(Entity { value: Some(date), ..entity("date") })
+            }
+            Pattern::MoneyBefore | Pattern::MoneyAfter => {
+                let currency = CURRENCIES.iter().find(|(symbol, _)| *symbol == group("currency")).map(|(_, code)| *code)?;
+                Some(Entity { amount: Some(EntityExtractor::amount(group("amount"))?), currency: Some(currency), ..entity("money") })
+            }
+            Pattern::Percentage => Some(Entity { amount: Some(EntityExtractor::amount(group("amount"))?), ..entity("percentage") }),
+        }
+    }
+
+    fn month(name: &str) -> Option<u32> {
+        let name = name.trim_end_matches('.').to_lowercase();
+        MONTH_NAMES.iter().find(|(month_name, _)| *month_name == name).map(|(_, month)| *month)
+    }
+
+    // Format a valid date in ISO 8601 format
+    pub fn date(year: u32, month: u32, day: u32) -> Option<String> {
+        let is_leap_year = year.is_multiple_of(4) && (!year.is_multiple_of(100) || year.is_multiple_of(400));
+        let days = match month {
+            1 | 3 | 5 | 7 | 8 | 10 | 12 => 31,
+            4 | 6 | 9 | 11 => 30,
+            2 if is_leap_year => 29,
+            2 => 28,
+            _ => return None,
+        };
+        (1..=days).contains(&day).then(|| format!("{:04}-{:02}-{:02}", year, month, day))
+    }
+
+    // Read an amount with thousands separators. The last separator is the decimal one if it is followed by one or two digits.
+    fn amount(text: &str) -> Option<f64> {
+        let decimal_separator = text.rfind(['.', ',']).filter(|position| (2..=3).contains(&(text.len() - position)));
+        let number: String = text
+            .char_indices()
+            .filter_map(|(index, ch)| match ch {
+                '0'..='9' => Some(ch),
+                _ if Some(index) == decimal_separator => Some('.'),
+                _ => None,
+            })
+            .collect();
+        number.parse().ok()
+    }
+
+    fn patterns() -> &'static Vec<(Pattern, Regex)> {
+        static REGEXES: OnceLock<Vec<(Pattern, Regex)>> = OnceLock::new();
+        REGEXES.get_or_init(|| {
+            let mut month_names: Vec<&str> = MONTH_NAMES.iter().map(|(name, _)| *name).collect();
+            month_names.sort_by_key(|name| std::cmp::Reverse(name.len()));
+            let month_name = format!(r"(?P<month_name>(?i:{})\.?)", month_names.join("|"));
+            let currency = format!(r"(?P<currency>{})", CURRENCIES.iter().map(|(symbol, _)| regex::escape(symbol)).collect::<Vec<String>>().join("|"));
+            let amount = format!("(?P<amount>{})", AMOUNT_PATTERN);
+
+            let patterns = [
+                (Pattern::Url, String::from(r#"\b(?:https?://|www\.)[^\s<>"']*[^\s<>"'.,;:!?)\]]"#)),
+                (Pattern::Email, String::from(EMAIL_PATTERN)),
+                (Pattern::IsoDate, String::from(r"\b(?P<year>\d{4})-(?P<month>\d{1,2})-(?P<day>\d{1,2})\b")),
+                (Pattern::DottedDate, String::from(r"\b(?P<day>\d{1,2})\. ?(?P<month>\d{1,2})\. ?(?P<year>\d{4})\b")),
+                (Pattern::YearFirstDottedDate, String::from(r"\b(?P<year>\d{4})\. ?(?P<month>\d{1,2})\. ?(?P<day>\d{1,2})\.")),
+                (Pattern::SlashDate, String::from(r"\b(?P<first>\d{1,2})/(?P<second>\d{1,2})/(?P<year>\d{4})\b")),
+                (Pattern::DayMonthDate, format!(r"\b(?P<day>\d{{1,2}})(?:st|nd|rd|th|\.)? {} (?P<year>\d{{4}})\b", month_name)),
+                (Pattern::MonthDayDate, format!(r"\b{} (?P<day>\d{{1,2}})(?:st|nd|rd|th)?,? (?P<year>\d{{4}})\b", month_name)),
+                (Pattern::MoneyBefore, format!(r"{} ?{}", currency, amount)),
+                (Pattern::MoneyAfter, format!(r"\b{} ?{}", amount, currency)),
+                (Pattern::Percentage, format!(r"\b{} ?(?:%|percent\b|per cent\b|Prozent\b)", amount)),
+                (Pattern::Phone, String::from(PHONE_PATTERN)),
+            ];
+            patterns.into_iter().map(|(pattern, regex)| (pattern, Regex::new(&regex).expect("Patterns are valid."))).collect()
+        })
+    }
+}
+
+impl Analyzer for EntityExtractor {
+    fn add(&mut self, fragment: &Fragment) {
+        EntityExtractor::add(self, &fragment.text);
+    }
+
+    fn write_result(&mut self, _parameters: &ExtractParameters, _language: Option<&str>, output: &mut String) {
+        output.push_str(&JsonWriter::array_field("entities", &self.entities));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_entities() {
+        let mut extractor = EntityExtractor::default();
+        extractor.add("See www.example.com/docs or mail Info@Example.com.");
+        extractor.add("Released on March 5, 2024 (05.03.2024, 3/15/2024, 2024-02-30) for $1,299.99 or 1.200,50 € with 12.5% off. Call +1 555 123 4567.");
+
+        // Offsets are counted in the text of the blocks separated by new line characters, and in the text of each block
+        let entities: Vec<(&str, &str, usize, usize, usize, usize)> = extractor
+            .entities
+            .iter()
+            .map(|entity| (entity.kind, entity.text.as_str(), entity.start, entity.end, entity.block, entity.block_start))
+            .collect();
+        assert_eq!(entities, vec![
+            ("url", "www.example.com/docs", 4, 24, 0, 4),
+            ("email", "Info@Example.com", 33, 49, 0, 33),
+            ("date", "March 5, 2024", 63, 76, 1, 12),
+            ("date", "05.03.2024", 78, 88, 1, 27),
+            ("date", "3/15/2024", 90, 99, 1, 39),
+            ("money", "$1,299.99", 117, 126, 1, 66),
+            ("money", "1.200,50 €", 130, 140, 1, 79),
+            ("percentage", "12.5%", 146, 151, 1, 95),
+            ("phone", "+1 555 123 4567", 162, 177, 1, 111)
+        ]);
+        let values: Vec<Option<&str>> = extractor.entities.iter().map(|entity| entity.value.as_deref()).collect();
+        assert_eq!(values[..5], [Some("http://www.example.com/docs"), Some("info@example.com"), Some("2024-03-05"), Some("2024-03-05"), Some("2024-03-15")]);
+        assert_eq!((extractor.entities[5].amount, extractor.entities[5].currency), (Some(1299.99), Some("USD")));
+        assert_eq!((extractor.entities[6].amount, extractor.entities[6].currency), (Some(1200.5), Some("EUR")));
+        assert_eq!(extractor.entities[7].amount, Some(12.5));
+    }
+
+    #[test]
+    fn test_dates_and_amounts() {
+        assert_eq!(EntityExtractor::date(2024, 2, 29).as_deref(), Some("2024-02-29"));
+        assert_eq!(EntityExtractor::date(2023, 2, 29), None);
+        assert_eq!(EntityExtractor::date(1900, 2, 29), None);
+        assert_eq!(EntityExtractor::date(2000, 2, 29).as_deref(), Some("2000-02-29"));
+        assert_eq!(EntityExtractor::date(2024, 13, 1), None);
+        assert_eq!(EntityExtractor::month("Oct."), Some(10));
+        assert_eq!(EntityExtractor::month("Juillet"), Some(7));
+        assert_eq!(EntityExtractor::amount("1,299.99"), Some(1299.99));
+        assert_eq!(EntityExtractor::amount("1.200"), Some(1200.0));
+        assert_eq!(EntityExtractor::amount("1.200,5"), Some(1200.5));
+    }
+}
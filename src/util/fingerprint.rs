@@ -0,0 +1,169 @@
+use serde::{Deserialize, Serialize};
+use std::collections::VecDeque;
+
+use super::{analyzer::Analyzer, fragment::Fragment, json_writer::JsonWriter};
+use crate::text_extractor::ExtractParameters;
+
+// Number of hash functions of the MinHash signature
+const MINHASH_SIZE: usize = 64;
+
+// Signatures of a text used to find near-duplicate documents without storing their text
+#[derive(Serialize, Deserialize)]
+pub struct Fingerprint {
+    // Number of words of the shingles (only fingerprints with the same shingle size can be compared)
+    pub shingle_size: usize,
+    // SimHash of the shingles as 16 hexadecimal digits
+    pub simhash: String,
+    // Minimum hash values of the shingles by each hash function
+    pub minhash: Vec<u32>,
+}
+
+// Similarity of two fingerprints
+#[derive(Serialize)]
+pub struct Similarity {
+    // Number of different bits of the SimHash values
+    pub simhash_distance: u32,
+    pub simhash_similarity: f64,
+    // Estimated Jaccard similarity of the shingles
+    pub minhash_similarity: f64,
+}
+
+impl Fingerprint {
+    pub fn compare(&self, other: &Fingerprint) -> Result<Similarity, String> {
+        if self.shingle_size != other.shingle_size {
+            return Err(format!("Fingerprints must have the same shingle size ({} and {})", self.shingle_size, other.shingle_size));
+        }
+        let parse = |simhash: &str| u64::from_str_radix(simhash, 16).map_err(|_| format!("Invalid SimHash: {}", simhash));
+        let simhash_distance = (parse(&self.simhash)? ^ parse(&other.simhash)?).count_ones();
+        if self.minhash.len() != other.minhash.len() || self.minhash.is_empty() {
+            return Err(String::from("MinHash signatures must have the same size"));
+        }
+        let equal_values = self.minhash.iter().zip(other.minhash.iter()).filter(|(a, b)| a == b).count();
+
+        let round = |value: f64| (value * 100.0).round() / 100.0;
+        Ok(Similarity {
+            simhash_distance,
+            simhash_similarity: round(1.0 - simhash_distance as f64 / 64.0),
+            minhash_similarity: round(equal_values as f64 / self.minhash.len() as f64),
+        })
+    }
+}
+
+// Compute the fingerprint of a text written in parts. Shingles are the sequences of a fixed number of words (in lowercase).
+pub struct FingerprintBuilder {
+    shingle_size: usize,
+    // Last words of the text (the next shingle without its last word)
+    words: VecDeque<String>,
+    shingle_count: usize,
+    // Sum of the bits of the shingle hashes (+1 for a set bit and -1 for a cleared one)
+    bit_weights: [i64; 64],
+    minimums: [u64; MINHASH_SIZE],
+}
+
+impl FingerprintBuilder {
+    pub fn new(shingle_size: usize) -> Self {
+        Self { shingle_size: shingle_size.max(1), words: VecDeque::new(), shingle_count: 0, bit_weights: [0; 64], minimums: [u64::MAX; MINHASH_SIZE] }
+    }
+
+    pub fn add(&mut self, text: &str) {
+        for word in text.split(|ch: char| !ch.is_alphanumeric()).filter(|word| !word.is_empty()) {
+            self.words.push_back(word.to_lowercase());
+            if self.words.len() > self.shingle_size {
+                self.words.pop_front();
+            }
+            if self.words.len() == self.shingle_size {
+                self.add_shingle();
+            }
+        }
+    }
+
+    // Texts shorter than a shingle are fingerprinted as one shorter shingle
+    pub fn finish(&mut self) -> Fingerprint {
+        if self.shingle_count == 0 && !self.words.is_empty() {
+            self.add_shingle();
+        }
+        let simhash = self.bit_weights.iter().enumerate().filter(|(_, weight)| **weight > 0).fold(0u64, |hash, (bit, _)| hash | (1 << bit));
+        Fingerprint { shingle_size: self.shingle_size, simhash: format!("{:016x}", simhash), minhash: self.minimums.iter().map(|minimum| (minimum >> 32) as u32).collect() }
+    }
+
+    fn add_shingle(&mut self) {
+        let hash = FingerprintBuilder::hash(&self.words);
+        for (bit, weight) in self.bit_weights.iter_mut().enumerate() {
+            *weight += if hash & (1 << bit) != 0 { 1 } else { -1 };
+        }
+        for (index, minimum) in self.minimums.iter_mut().enumerate() {
+            *minimum = (*minimum).min(FingerprintBuilder::mix(hash ^ (index as u64 + 1).wrapping_mul(0x9E37_79B9_7F4A_7C15)));
+        }
+        self.shingle_count += 1;
+    }
+
+    // FNV-1a hash of the words (a stable hash is needed to compare fingerprints computed by different versions)
+    fn hash(words: &VecDeque<String>) -> u64 {
+        let mut hash: u64 = 0xCBF2_9CE4_8422_2325;
+        for word in words.iter() {
+            for byte in word.bytes().chain(std::iter::once(b' ')) {
+                hash ^= byte as u64;
+                hash = hash.wrapping_mul(0x0100_0000_01B3);
+            }
+        }
+        FingerprintBuilder::mix(hash)
+    }
+
+    // Finalizer of SplitMix64 deriving independent hash functions from one hash
+    fn mix(value: u64) -> u64 {
+        let mut value = value;
+        value = (value ^ (value >> 30)).wrapping_mul(0xBF58_476D_1CE4_E5B9);
+        value = (value ^ (value >> 27)).wrapping_mul(0x94D0_49BB_1331_11EB);
+        value ^ (value >> 31)
+    }
+}
+
+impl Analyzer for FingerprintBuilder {
+    fn add(&mut self, fragment: &Fragment) {
+        FingerprintBuilder::add(self, &fragment.text);
+    }
+
+    fn write_result(&mut self, _parameters: &ExtractParameters, _language: Option<&str>, output: &mut String) {
+        output.push_str(&JsonWriter::field("fingerprint", &self.finish()));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn fingerprint(texts: &[&str], shingle_size: usize) -> Fingerprint {
+        let mut builder = FingerprintBuilder::new(shingle_size);
+        texts.iter().for_each(|text| builder.add(text));
+        builder.finish()
+    }
+
+    #[test]
+    fn test_similarity() {
+        let text = "The city council announced on Monday that the new bridge over the river will open to traffic at the end of the year. \
+            The mayor said the project was delayed by the weather and rising steel prices.";
+        let original = fingerprint(&[text], 3);
+        assert_eq!(original.simhash.len(), 16);
+        assert_eq!(original.minhash.len(), 64);
+
+        // Texts written in parts have the same fingerprint, and the case of the words and punctuation don't matter
+        let identical = original.compare(&fingerprint(&["THE CITY COUNCIL announced on Monday", &text[37..]], 3)).unwrap();
+        assert_eq!((identical.simhash_distance, identical.simhash_similarity, identical.minhash_similarity), (0, 1.0, 1.0));
+
+        let near_duplicate = original.compare(&fingerprint(&["Home", text], 3)).unwrap();
+        assert!(near_duplicate.simhash_distance < 16 && near_duplicate.minhash_similarity > 0.7);
+        let unrelated = original.compare(&fingerprint(&["Batteries store the electricity generated by solar panels during the day for use at night."], 3)).unwrap();
+        assert!(unrelated.simhash_distance > near_duplicate.simhash_distance && unrelated.minhash_similarity < 0.2);
+    }
+
+    #[test]
+    fn test_compare_errors() {
+        let original = fingerprint(&["Short"], 3);
+        assert!(original.compare(&fingerprint(&["Short"], 3)).is_ok());
+        assert!(original.compare(&fingerprint(&["Short"], 2)).is_err());
+        let invalid = Fingerprint { simhash: String::from("xyz"), ..fingerprint(&["Short"], 3) };
+        assert!(original.compare(&invalid).is_err());
+        let truncated = Fingerprint { minhash: Vec::new(), ..fingerprint(&["Short"], 3) };
+        assert!(original.compare(&truncated).is_err());
+    }
+}
@@ -0,0 +1,105 @@
+use crate::text_extractor::InputFormat;
+
+// Number of bytes read from the beginning of the body to detect its format
+pub const SNIFF_LENGTH: usize = 1024;
+
+// Signatures of binary formats at the beginning of the files
+const SIGNATURES: [(&[u8], InputFormat); 4] = [
+    (b"%PDF-", InputFormat::Pdf),
+    (b"PK\x03\x04", InputFormat::Zip),
+    (b"PK\x05\x06", InputFormat::Zip),
+    (b"\x1f\x8b", InputFormat::Gzip),
+];
+
+// Detect the format of a document from its declared media type or from its first bytes
+pub struct FormatDetector {}
+
+impl FormatDetector {
+    // The declared media type is used if it is specific, otherwise the content is sniffed. XML documents are checked
+    // whether they are feeds.
+    pub fn detect(content_type: Option<&str>, head: &[u8]) -> InputFormat {
+        match content_type.and_then(FormatDetector::from_content_type) {
+            Some(InputFormat::Xml) => FormatDetector::sniff(head).filter(|format| *format == InputFormat::Feed).unwrap_or(InputFormat::Xml),
+            Some(format) => format,
+            None => FormatDetector::sniff(head).unwrap_or(InputFormat::Html),
+        }
+    }
+
+    // Format of a media type (parameters like the charset are ignored)
+    pub fn from_content_type(content_type: &str) -> Option<InputFormat> {
+        let media_type = content_type.split(';').next().unwrap_or_default().trim().to_lowercase();
+        match media_type.as_str() {
+            "text/html" | "application/xhtml+xml" => Some(InputFormat::Html),
+            "application/rss+xml" | "application/atom+xml" | "application/rdf+xml" => Some(InputFormat::Feed),
+            "text/xml" | "application/xml" => Some(InputFormat::Xml),
+            "application/pdf" => Some(InputFormat::Pdf),
+            "application/zip" | "application/epub+zip" => Some(InputFormat::Zip),
+            "application/gzip" | "application/x-gzip" => Some(InputFormat::Gzip),
+            _ if media_type.ends_with("+xml") => Some(InputFormat::Xml),
+            _ if media_type.starts_with("application/vnd.openxmlformats-officedocument.") => Some(InputFormat::Zip),
+            _ => None,
+        }
+    }
+
+    // Detect the format from the signatures of binary formats and the markup at the beginning of text documents
+    pub fn sniff(head: &[u8]) -> Option<InputFormat> {
+        if let Some((_, format)) = SIGNATURES.iter().find(|(signature, _)| head.starts_with(signature)) {
+            return Some(*format);
+        }
+
+        // Bytes of an incomplete character at the end are ignored
+        let head = head.strip_prefix(b"\xef\xbb\xbf").unwrap_or(head);
+        let text = match std::str::from_utf8(head) {
+            Ok(text) => text,
+            Err(error) => std::str::from_utf8(&head[..error.valid_up_to()]).unwrap_or_default(),
+        };
+        let text = text.trim_start();
+        let lowercase = text.to_lowercase();
+        if lowercase.starts_with("<!doctype html") {
+            return Some(InputFormat::Html);
+        }
+        let is_xml = text.starts_with("<?xml");
+
+        // The first element after the prolog (comments, processing instructions and doctype) decides
+        let mut rest = text;
+        while let Some(start) = rest.find('<') {
+            rest = &rest[start + 1..];
+            if rest.starts_with(|ch: char| ch.is_alphabetic()) {
+                let name: String = rest.chars().take_while(|ch| !ch.is_whitespace() && *ch != '>' && *ch != '/').collect();
+                return match name.rsplit(':').next().unwrap_or_default().to_lowercase().as_str() {
+                    "rss" => Some(InputFormat::Feed),
+                    "feed" if text.contains("http://www.w3.org/2005/Atom") => Some(InputFormat::Feed),
+                    "rdf" if text.contains("http://purl.org/rss/1.0/") => Some(InputFormat::Feed),
+                    "html" | "head" | "body" => Some(InputFormat::Html),
+                    _ if is_xml => Some(InputFormat::Xml),
+                    _ => None,
+                };
+            }
+        }
+        is_xml.then_some(InputFormat::Xml)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_detect() {
+        // Formats are sniffed from the beginning of the body
+        assert_eq!(FormatDetector::detect(None, b"<?xml version=\"1.0\"?><rss version=\"2.0\"><channel></channel></rss>"), InputFormat::Feed);
+        assert_eq!(FormatDetector::detect(None, b"<?xml version=\"1.0\"?><!-- Books --><catalog></catalog>"), InputFormat::Xml);
+        assert_eq!(FormatDetector::detect(None, b"<feed xmlns=\"http://www.w3.org/2005/Atom\"><title>News</title></feed>"), InputFormat::Feed);
+        assert_eq!(FormatDetector::detect(None, b"\xef\xbb\xbf  <!DOCTYPE html><html><body>Page</body></html>"), InputFormat::Html);
+        assert_eq!(FormatDetector::detect(None, b"%PDF-1.7\n%\xe2\xe3\xcf\xd3"), InputFormat::Pdf);
+        assert_eq!(FormatDetector::detect(None, b"\x1f\x8b\x08\x00"), InputFormat::Gzip);
+        assert_eq!(FormatDetector::detect(None, b"Plain text"), InputFormat::Html);
+
+        // The declared media type is used before sniffing, but XML documents can still be feeds
+        assert_eq!(FormatDetector::detect(Some("application/xml; charset=utf-8"), b"<body><p>Data</p></body>"), InputFormat::Xml);
+        assert_eq!(FormatDetector::detect(Some("text/xml"), b"<rss version=\"2.0\"></rss>"), InputFormat::Feed);
+        assert_eq!(FormatDetector::detect(Some("TEXT/HTML"), b"<?xml version=\"1.0\"?><rss></rss>"), InputFormat::Html);
+        assert_eq!(FormatDetector::detect(Some("image/svg+xml"), b""), InputFormat::Xml);
+        assert_eq!(FormatDetector::detect(Some("application/octet-stream"), b"PK\x03\x04"), InputFormat::Zip);
+    }
+}
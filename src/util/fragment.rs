@@ -0,0 +1,99 @@
+use serde::Serialize;
+
+use super::stats::{TextCounter, TextCounts};
+
+// A separated block of the extracted text
+#[derive(Default)]
+pub struct Fragment {
+    // Tag of the element starting the fragment (like <p>)
+    pub separator: String,
+    pub text: String,
+    // Number of characters written inside links
+    pub link_length: usize,
+    // Score of the elements containing the fragment, used to detect the main content
+    pub hint: i32,
+    // Final score of the fragment if the main content was detected
+    pub score: Option<f64>,
+    // Level of the heading (1-6) if the fragment is the text of a heading
+    pub heading_level: Option<u8>,
+    // Identifiers of the sectioning elements (like section and article) containing the fragment
+    pub sections: Vec<usize>,
+    // Whether the fragment is the text of the document title
+    pub title: bool,
+    // Whether the fragment is the text of a code block with whitespace kept as it is
+    pub code: bool,
+    // Formatted parts of the text (like bold or linked words)
+    pub spans: Vec<Span>,
+    // Path of the block element containing the fragment (like 'html > body > div#main > p')
+    pub path: Option<String>,
+    // Text of the last heading before the fragment
+    pub heading: Option<String>,
+    // Language declared by the lang attribute of the elements containing the fragment (or by the whole document)
+    pub declared_language: Option<String>,
+    // Counts of the text collected while it is written (if statistics are requested) and the final counts
+    pub counter: Option<TextCounter>,
+    pub counts: TextCounts,
+    // Whether the text was changed after it was written (its counts are taken from the final text then)
+    pub rewritten: bool,
+}
+
+// Formatting of a part of the fragment text between the start and end character offsets
+#[derive(Serialize, Clone)]
+pub struct Span {
+    pub kind: String,
+    pub start: usize,
+    pub end: usize,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub href: Option<String>,
+}
+
+impl Fragment {
+    pub fn new(separator: String) -> Self {
+        Self {
+            separator,
+            ..Default::default()
+        }
+    }
+
+    pub fn push(&mut self, ch: char) {
+        self.text.push(ch);
+        if let Some(counter) = self.counter.as_mut() {
+            counter.push(ch);
+        }
+    }
+
+    // Replace the text between two character offsets and move the spans after it
+    pub fn replace_range(&mut self, start: usize, end: usize, to: &str) {
+        self.rewritten = true;
+        let byte_offset = |text: &str, offset: usize| text.char_indices().nth(offset).map_or(text.len(), |(index, _)| index);
+        let (byte_start, byte_end) = (byte_offset(&self.text, start), byte_offset(&self.text, end));
+        self.text.replace_range(byte_start..byte_end, to);
+
+        let to_length = to.chars().count();
+        let move_offset = |offset: usize| if offset <= start { offset } else if offset >= end { offset + to_length - (end - start) } else { start + to_length };
+        for span in self.spans.iter_mut() {
+            span.start = move_offset(span.start);
+            span.end = move_offset(span.end);
+        }
+    }
+
+    // Replace all occurrences of a text and move the spans after them
+    pub fn replace(&mut self, from: &str, to: &str) {
+        let (from_length, to_length) = (from.chars().count(), to.chars().count());
+        let mut search_start = 0;
+        while let Some(position) = self.text[search_start..].find(from).map(|position| position + search_start) {
+            let offset = self.text[..position].chars().count();
+            self.text.replace_range(position..position + from.len(), to);
+            self.rewritten = true;
+            for span in self.spans.iter_mut() {
+                if span.start >= offset + from_length {
+                    span.start = span.start + to_length - from_length;
+                }
+                if span.end >= offset + from_length {
+                    span.end = span.end + to_length - from_length;
+                }
+            }
+            search_start = position + to.len();
+        }
+    }
+}
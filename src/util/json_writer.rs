@@ -1,105 +1,567 @@
-use tokio::io::AsyncWrite;
+use serde::Serialize;
+use std::any::Any;
 
-use crate::{error::Error, text_extractor::OutputFormat};
-use super::utf8_writer::Utf8Writer;
+use crate::text_extractor::{ContentMode, ExtractParameters, Granularity, OutputFormat};
+use super::{analyzer::{self, Analyzer, TextCollector}, chunker::Chunker, fingerprint::{Fingerprint, FingerprintBuilder}, fragment::{Fragment, Span}, language::Language, main_content::MainContent, normalizer::{Cleaned, TextNormalizer}, redactor::Redactor, section_tree::SectionTree, sentences::SentenceSplitter, stats::{TextCounter, TextCounts}, table::Table};
 
-pub struct JsonWriter<'a, W: AsyncWrite + Unpin + Send> {
-    utf8_writer: Utf8Writer<'a, W>,
+// Length of the document text (in bytes) used to detect its language
+const LANGUAGE_SAMPLE_LENGTH: usize = 10000;
+
+// Collects the extracted text into fragments and serializes them to JSON. A fragment is serialized when the next one
+// starts, unless the whole document must be known to decide what to output (like in the main content mode). Analyses
+// of the whole document are done by the analyzers fed with the fragments written to the output.
+pub struct JsonWriter {
+    parameters: ExtractParameters,
+    normalizer: TextNormalizer,
+    redactor: Redactor,
+    // Serialized JSON not written to the output stream yet
+    output: String,
+    fragment: Fragment,
+    // Completed fragments waiting for the end of the document
+    fragments: Vec<Fragment>,
+    written_fragment_count: usize,
+    // Number of text blocks written in sentences
+    block_count: usize,
+    link_depth: usize,
+    heading_level: Option<u8>,
+    title: bool,
+    // Identifiers of the open sectioning elements
+    sections: Vec<usize>,
+    section_count: usize,
+    // Open elements with whitespace written to the output without collapsing, and whether they contain code
+    preformatted: Vec<bool>,
+    tables: Vec<Table>,
+    // Language declared for the whole document and the languages of the open elements with lang attribute
+    document_language: Option<String>,
+    languages: Vec<String>,
+    // Beginning of the document text used to detect its language
+    language_sample: String,
+    analyzers: Vec<Box<dyn Analyzer>>,
+    // Text of the last completed heading fragment
+    last_heading: Option<String>,
+    // Spans of the open formatting elements, started by the first character written inside them
+    open_spans: Vec<OpenSpan>,
+    // Text written at the start of the next fragment (like the bullet of a list item)
+    prefix: Option<String>,
+    // Number of hold calls without release and the fragments completed since the first one (they are processed after
+    // the release, when their text is final)
+    hold_depth: usize,
+    held_fragments: Vec<Fragment>,
     line_contains_whitespace_only: bool,
     new_separator: Option<String>,
     last_caharcter_was_whitespace: bool,
     whitespace_to_write: Option<char>,
-    output_format: OutputFormat
 }
 
-impl<'a, W: AsyncWrite + Unpin + Send> JsonWriter<'a, W> {
-    pub fn new(utf8_writer: Utf8Writer<'a, W>, output_format: OutputFormat) -> Self {
+impl JsonWriter {
+    pub fn new(parameters: &ExtractParameters) -> Self {
         Self {
-            utf8_writer,
+            parameters: parameters.clone(),
+            normalizer: TextNormalizer::new(parameters),
+            redactor: Redactor::default(),
+            output: String::new(),
+            fragment: Fragment::default(),
+            fragments: Vec::new(),
+            written_fragment_count: 0,
+            block_count: 0,
+            link_depth: 0,
+            heading_level: None,
+            title: false,
+            sections: Vec::new(),
+            section_count: 0,
+            preformatted: Vec::new(),
+            tables: Vec::new(),
+            document_language: parameters.content_language.clone(),
+            languages: Vec::new(),
+            language_sample: String::new(),
+            analyzers: analyzer::analyzers(parameters),
+            last_heading: None,
+            open_spans: Vec::new(),
+            prefix: None,
+            hold_depth: 0,
+            held_fragments: Vec::new(),
             line_contains_whitespace_only: true,
             new_separator: None,
             last_caharcter_was_whitespace: true,
             whitespace_to_write: None,
-            output_format,
         }
     }
 
-    pub async fn start(&mut self) -> Result<(), Error> {
-        self.utf8_writer
-            .write_string("{\n \"results\": [\n  {\n   \"text\": \"")
-            .await
+    pub fn start(&mut self) {
+        self.output.push_str("{\n \"results\": [\n");
+    }
+
+    // Return the JSON serialized since the last call
+    pub fn take_output(&mut self) -> String {
+        std::mem::take(&mut self.output)
+    }
+
+    // Check whether there is serialized JSON to take (it is produced when a fragment is completed)
+    pub fn has_output(&self) -> bool {
+        !self.output.is_empty()
     }
 
     #[inline]
-    pub async fn write_char(&mut self, ch: char) -> Result<(), Error> {
-        if !ch.is_whitespace() {
+    pub fn write_char(&mut self, ch: char) {
+        match self.normalizer.clean(ch) {
+            Cleaned::Char(ch) => self.push_char(ch),
+            Cleaned::Text(text) => text.chars().for_each(|ch| self.push_char(ch)),
+            Cleaned::Removed => {}
+        }
+    }
+
+    #[inline]
+    fn push_char(&mut self, ch: char) {
+        if self.is_preformatted() {
+            if let Some(whitespace) = self.whitespace_to_write.take() {
+                self.fragment.push(whitespace);
+            }
+            self.line_contains_whitespace_only = false;
+            self.last_caharcter_was_whitespace = ch.is_whitespace();
+        } else if !ch.is_whitespace() {
             self.line_contains_whitespace_only = false;
             if let Some(whitespace) = self.whitespace_to_write {
-                if whitespace == '\n' {
-                    self.utf8_writer.write_string("\\n").await?;
-                } else {
-                    self.utf8_writer.write_char(whitespace).await?;
-                }
-
+                self.fragment.push(whitespace);
                 self.whitespace_to_write = None;
             }
             self.last_caharcter_was_whitespace = false;
         } else {
             if self.line_contains_whitespace_only || self.last_caharcter_was_whitespace {
-                return Ok(());
-            }
-            if !self.last_caharcter_was_whitespace {
-                self.last_caharcter_was_whitespace = true;
-
-                self.whitespace_to_write = Some(ch);
-                return Ok(());
+                return;
             }
-
             self.last_caharcter_was_whitespace = true;
+            self.whitespace_to_write = Some(ch);
+            return;
         }
 
-        if self.new_separator.is_some() {
-            if self.output_format == OutputFormat::Advanced {
-                let separator = self.new_separator.as_ref().unwrap();
-                self.utf8_writer.write_string("\"\n  },\n  {\n").await?;
-                if !separator.is_empty() {
-                    self.utf8_writer
-                        .write_string(&format!("   \"separator\": \"{}\",\n", separator))
-                        .await?;
-                }
-                self.utf8_writer.write_string("   \"text\": \"").await?;
-            } else {
-                self.utf8_writer.write_string("\\n").await?;
+        if let Some(separator) = self.new_separator.take() {
+            self.finish_fragment(Fragment::new(separator));
+        }
+
+        // Structure of the document is taken from the elements containing the first character of the fragment
+        if self.fragment.text.is_empty() {
+            self.fragment.heading_level = self.heading_level;
+            self.fragment.title = self.title;
+            self.fragment.sections.clone_from(&self.sections);
+            self.fragment.code = self.preformatted.iter().any(|code| *code);
+            if self.parameters.paths {
+                self.fragment.heading.clone_from(&self.last_heading);
             }
+            if self.parameters.detect_language || self.parameters.stats {
+                self.fragment.declared_language = self.languages.last().or(self.document_language.as_ref()).filter(|language| !language.is_empty()).cloned();
+            }
+            if self.parameters.stats {
+                let language = TextCounts::primary_language(self.fragment.declared_language.as_deref());
+                self.fragment.counter = Some(TextCounter::new(language));
+            }
+            if let Some(prefix) = self.prefix.take() {
+                prefix.chars().for_each(|ch| self.fragment.push(ch));
+            }
+        }
 
-            self.new_separator = None;
+        if self.open_spans.iter().any(|span| span.start.is_none()) {
+            let offset = self.fragment.text.chars().count();
+            self.open_spans.iter_mut().filter(|span| span.start.is_none()).for_each(|span| span.start = Some(offset));
         }
 
-        match ch {
-            '"' => self.utf8_writer.write_string("\\\"").await,
-            '\\' => self.utf8_writer.write_string("\\\\").await,
-            '\n' => self.utf8_writer.write_string("\\n").await,
-            _ => self.utf8_writer.write_char(ch).await,
+        self.fragment.push(ch);
+        if self.link_depth > 0 {
+            self.fragment.link_length += 1;
         }
     }
 
-    pub async fn add_break(&mut self, separator: &str) -> Result<(), Error> {
+    // Check whether writing the character starts a new fragment
+    pub fn is_fragment_start(&self, ch: char) -> bool {
+        let ch = match self.normalizer.clean(ch) {
+            Cleaned::Char(ch) => ch,
+            Cleaned::Text(text) => text.chars().next().unwrap_or(' '),
+            Cleaned::Removed => return false,
+        };
+        (self.is_preformatted() || !ch.is_whitespace()) && (self.new_separator.is_some() || self.fragment.text.is_empty())
+    }
+
+    pub fn fragment_mut(&mut self) -> &mut Fragment {
+        &mut self.fragment
+    }
+
+    pub fn add_break(&mut self, separator: &str) {
         self.whitespace_to_write = None;
         self.last_caharcter_was_whitespace = true;
         self.new_separator = Some(String::from(separator));
         self.line_contains_whitespace_only = true;
-        Ok(())
     }
 
-    pub async fn end(&mut self, error_message: &str) -> Result<(), Error> {
-        self.utf8_writer.write_string("\"\n  }\n").await?;
-        self.utf8_writer.write_string(" ]").await?;
+    // Text written between start_link and end_link calls is counted as the text of links
+    pub fn start_link(&mut self) {
+        self.link_depth += 1;
+    }
+
+    pub fn end_link(&mut self) {
+        self.link_depth = self.link_depth.saturating_sub(1);
+    }
+
+    // Text written between start_heading and end_heading calls is the text of a heading
+    pub fn start_heading(&mut self, level: u8) {
+        self.heading_level = Some(level);
+    }
+
+    pub fn end_heading(&mut self) {
+        self.heading_level = None;
+    }
+
+    // Text written between start_title and end_title calls is the title of the document
+    pub fn start_title(&mut self) {
+        self.title = true;
+    }
+
+    pub fn end_title(&mut self) {
+        self.title = false;
+    }
+
+    // Text written between start_section and end_section calls is nested into a section in the Sections format
+    pub fn start_section(&mut self) {
+        self.section_count += 1;
+        self.sections.push(self.section_count);
+    }
+
+    pub fn end_section(&mut self) {
+        self.sections.pop();
+    }
+
+    // Whitespace written between start_preformatted and end_preformatted calls is kept as it is. Fragments started
+    // inside code are flagged as code blocks.
+    pub fn start_preformatted(&mut self, code: bool) {
+        self.preformatted.push(code);
+    }
+
+    pub fn end_preformatted(&mut self) {
+        self.preformatted.pop();
+    }
+
+    pub fn is_preformatted(&self) -> bool {
+        !self.preformatted.is_empty()
+    }
+
+    // Set the language declared for the whole document (by the lang attribute of the html element)
+    pub fn set_document_language(&mut self, language: &str) {
+        self.document_language = Some(String::from(language));
+    }
+
+    // Text written between start_language and end_language calls is in the declared language
+    pub fn start_language(&mut self, language: &str) {
+        self.languages.push(String::from(language));
+    }
+
+    pub fn end_language(&mut self) {
+        self.languages.pop();
+    }
+
+    // Text written between start_span and end_span calls is annotated with a span of the fragment
+    pub fn start_span(&mut self, kind: &str, href: Option<&str>) {
+        self.open_spans.push(OpenSpan { kind: String::from(kind), href: href.map(String::from), start: None });
+    }
+
+    pub fn end_span(&mut self) {
+        if let Some(span) = self.open_spans.pop() {
+            if let Some(start) = span.start {
+                let end = self.fragment.text.chars().count();
+                self.fragment.spans.push(Span { kind: span.kind, start, end, href: span.href });
+            }
+        }
+    }
+
+    // Set the text written at the start of the next fragment, or remove it if no fragment was started since it was set
+    pub fn set_prefix(&mut self, prefix: Option<String>) {
+        self.prefix = prefix;
+    }
+
+    // Fragments completed between hold and release calls are not written until the release, so their text can be
+    // changed after the end of the elements they were written in
+    pub fn hold(&mut self) {
+        self.hold_depth += 1;
+    }
+
+    // Replace texts in the held fragments (and the current one) and process them if no other hold is active
+    pub fn release(&mut self, replacements: &[(String, String)]) {
+        for fragment in self.held_fragments.iter_mut().chain(std::iter::once(&mut self.fragment)) {
+            for (from, to) in replacements {
+                fragment.replace(from, to);
+            }
+        }
+
+        self.hold_depth = self.hold_depth.saturating_sub(1);
+        if self.hold_depth == 0 {
+            for fragment in std::mem::take(&mut self.held_fragments) {
+                self.complete_fragment(fragment);
+            }
+        }
+    }
+
+    // Tables are written at the end of the output if they are requested
+    pub fn add_table(&mut self, table: Table) {
+        self.tables.push(table);
+    }
+
+    pub fn end(&mut self, error_message: &str) {
+        self.finish_fragment(Fragment::default());
+        for fragment in std::mem::take(&mut self.held_fragments) {
+            self.complete_fragment(fragment);
+        }
+
+        let fragments = std::mem::take(&mut self.fragments);
+        let fragments = match self.parameters.content_mode {
+            ContentMode::Main => MainContent::select(fragments),
+            ContentMode::Full => fragments,
+        };
+        match self.parameters.output_format {
+            OutputFormat::Sections => {
+                fragments.iter().for_each(|fragment| self.add_to_document(fragment));
+                let sections: Vec<String> = SectionTree::build(fragments).iter().map(|section| format!("  {}", JsonWriter::to_json(section))).collect();
+                self.output.push_str(&sections.join(",\n"));
+            }
+            OutputFormat::Chunks => {
+                fragments.iter().for_each(|fragment| self.add_to_document(fragment));
+                let chunker = Chunker::new(self.parameters.chunk_unit, self.parameters.chunk_size, self.parameters.chunk_overlap);
+                let chunks: Vec<String> = chunker.split(&fragments).iter().map(|chunk| format!("  {}", JsonWriter::to_json(chunk))).collect();
+                self.output.push_str(&chunks.join(",\n"));
+            }
+            OutputFormat::Simple | OutputFormat::Advanced => {
+                for fragment in fragments {
+                    self.write_fragment(fragment);
+                }
+            }
+        }
+
+        if self.parameters.output_format == OutputFormat::Simple {
+            if self.written_fragment_count == 0 {
+                self.output.push_str("  {\n   \"text\": \"");
+            }
+            self.output.push_str("\"\n  }");
+        }
+        self.output.push_str("\n ]");
+        if let Some(input_format) = self.parameters.input_format {
+            self.output.push_str(&JsonWriter::field("input_format", &input_format));
+        }
+        if self.parameters.tables {
+            self.output.push_str(&JsonWriter::array_field("tables", &self.tables));
+        }
+        let declared_language = self.document_language.clone().filter(|language| !language.is_empty());
+        // Language is only detected if it is reported or it is needed by an analyzer (the sample is collected for them only)
+        let detected_language = if self.needs_language() {
+            Language::new(declared_language.clone(), &self.language_sample)
+        } else {
+            Language { declared: declared_language.clone(), ..Default::default() }
+        };
+        if self.parameters.detect_language {
+            self.output.push_str(&JsonWriter::field("language", &detected_language));
+        }
+        if self.parameters.redact {
+            self.output.push_str(&JsonWriter::field("redactions", &self.redactor.counts));
+        }
+        // Results of the analyzers depend on the declared language, or the detected one if it is not declared
+        let language = declared_language.as_deref().or(detected_language.detected).map(|language| TextCounts::primary_language(Some(language)));
+        for analyzer in self.analyzers.iter_mut() {
+            analyzer.write_result(&self.parameters, language.as_deref(), &mut self.output);
+        }
         if !error_message.is_empty() {
-            self.utf8_writer
-                .write_string(&format!(",\n \"error\": \"{}\"", error_message))
-                .await?;
+            self.output.push_str(&JsonWriter::field("error", error_message));
+        }
+        self.output.push_str("\n}");
+    }
+
+    // Collect the text written to the output, so it can be taken without reading the serialized JSON
+    pub fn collect_text(&mut self) {
+        self.analyzers.push(Box::new(TextCollector::default()));
+    }
+
+    pub fn take_text(&mut self) -> String {
+        self.analyzer::<TextCollector>().map(|collector| std::mem::take(&mut collector.text)).unwrap_or_default()
+    }
+
+    // Fingerprint of the text written to the output if it is requested (it is complete when the end of the document is written)
+    pub fn fingerprint(&mut self) -> Option<Fingerprint> {
+        self.analyzer::<FingerprintBuilder>().map(FingerprintBuilder::finish)
+    }
+
+    fn analyzer<T: Analyzer>(&mut self) -> Option<&mut T> {
+        self.analyzers.iter_mut().find_map(|analyzer| (analyzer.as_mut() as &mut dyn Any).downcast_mut::<T>())
+    }
+
+    fn needs_language(&self) -> bool {
+        self.parameters.detect_language || self.analyzers.iter().any(|analyzer| analyzer.needs_language())
+    }
+
+    // Complete the current fragment and continue with the next one
+    fn finish_fragment(&mut self, next_fragment: Fragment) {
+        // Spans continuing in the next fragment are split
+        let end = self.fragment.text.chars().count();
+        for span in self.open_spans.iter_mut() {
+            if let Some(start) = span.start.take() {
+                self.fragment.spans.push(Span { kind: span.kind.clone(), start, end, href: span.href.clone() });
+            }
+        }
+        self.fragment.spans.sort_by_key(|span| span.start);
+
+        let fragment = std::mem::replace(&mut self.fragment, next_fragment);
+        if self.hold_depth > 0 {
+            self.held_fragments.push(fragment);
+        } else {
+            self.complete_fragment(fragment);
+        }
+    }
+
+    // Process the final text of a fragment and write it (or keep it until the end of the document)
+    fn complete_fragment(&mut self, mut fragment: Fragment) {
+        self.normalizer.normalize(&mut fragment);
+        if self.parameters.redact {
+            self.redactor.redact(&mut fragment);
+        }
+        if self.parameters.stats {
+            fragment.counts = match fragment.counter.take() {
+                Some(counter) if !fragment.rewritten => counter.finish(fragment.link_length),
+                _ => TextCounts::new(&fragment.text, fragment.link_length, &TextCounts::primary_language(fragment.declared_language.as_deref())),
+            };
+        }
+        if self.needs_language() && self.language_sample.len() < LANGUAGE_SAMPLE_LENGTH {
+            self.language_sample.push_str(&fragment.text);
+            self.language_sample.push('\n');
+        }
+        if self.parameters.paths && fragment.heading_level.is_some() && !fragment.text.is_empty() {
+            self.last_heading = Some(fragment.text.clone());
+        }
+
+        if self.is_buffered() {
+            self.fragments.push(fragment);
+        } else {
+            self.write_fragment(fragment);
+        }
+    }
+
+    // Fragments are written at the end of the document if the whole document must be known to decide what to write
+    fn is_buffered(&self) -> bool {
+        self.parameters.content_mode == ContentMode::Main || matches!(self.parameters.output_format, OutputFormat::Sections | OutputFormat::Chunks)
+    }
+
+    // Analyses of the document are fed with the fragments written to the output
+    fn add_to_document(&mut self, fragment: &Fragment) {
+        for analyzer in self.analyzers.iter_mut() {
+            analyzer.add(fragment);
+        }
+    }
+
+    fn write_fragment(&mut self, fragment: Fragment) {
+        self.add_to_document(&fragment);
+        match self.parameters.output_format {
+            // Simple format contains all fragments in one text separated by new line characters
+            OutputFormat::Simple => {
+                if self.written_fragment_count == 0 {
+                    self.output.push_str("  {\n   \"text\": \"");
+                } else {
+                    self.output.push_str("\\n");
+                }
+                let text = JsonWriter::to_json(&fragment.text);
+                self.output.push_str(&text[1..text.len() - 1]);
+                self.written_fragment_count += 1;
+            }
+            // Advanced format contains one object for each fragment or for each sentence of the fragments (Sections and Chunks
+            // formats are written by the end method)
+            OutputFormat::Advanced | OutputFormat::Sections | OutputFormat::Chunks => {
+                if self.parameters.granularity == Granularity::Sentence {
+                    self.write_sentences(fragment);
+                    return;
+                }
+
+                let mut fields = Vec::new();
+                if !fragment.separator.is_empty() {
+                    fields.push(("separator", JsonWriter::to_json(&fragment.separator)));
+                }
+                fields.push(("text", JsonWriter::to_json(&fragment.text)));
+                self.push_block_fields(&mut fields, &fragment, &fragment.text, &fragment.spans);
+                if self.parameters.stats {
+                    let language = TextCounts::primary_language(fragment.declared_language.as_deref());
+                    fields.push(("stats", JsonWriter::to_json(&fragment.counts.stats(&language))));
+                }
+                self.write_object(&fields);
+            }
+        }
+    }
+
+    // Write each sentence of a fragment with the index of the fragment and the offsets of the sentence in its text
+    fn write_sentences(&mut self, fragment: Fragment) {
+        let sentences = SentenceSplitter::split(&fragment.text);
+        if sentences.is_empty() {
+            return;
+        }
+        let chars: Vec<char> = fragment.text.chars().collect();
+        for (start, end) in sentences {
+            let text: String = chars[start..end].iter().collect();
+            let spans: Vec<Span> = fragment.spans.iter().filter(|span| span.start < end && span.end > start).cloned().collect();
+
+            let mut fields = vec![
+                ("text", JsonWriter::to_json(&text)),
+                ("block", JsonWriter::to_json(&self.block_count)),
+                ("start", JsonWriter::to_json(&start)),
+                ("end", JsonWriter::to_json(&end)),
+            ];
+            self.push_block_fields(&mut fields, &fragment, &text, &spans);
+            self.write_object(&fields);
         }
-        self.utf8_writer.write_string("\n}").await?;
-        Ok(())
+        self.block_count += 1;
     }
+
+    // Fields of the Advanced format describing the context of a text
+    fn push_block_fields(&self, fields: &mut Vec<(&str, String)>, fragment: &Fragment, text: &str, spans: &[Span]) {
+        if let Some(path) = &fragment.path {
+            fields.push(("path", JsonWriter::to_json(path)));
+        }
+        if let Some(heading) = &fragment.heading {
+            fields.push(("heading", JsonWriter::to_json(heading)));
+        }
+        if self.parameters.detect_language {
+            fields.push(("language", JsonWriter::to_json(&Language::new(fragment.declared_language.clone(), text))));
+        }
+        if !spans.is_empty() {
+            fields.push(("spans", JsonWriter::to_json(spans)));
+        }
+        if fragment.code {
+            fields.push(("code", JsonWriter::to_json(&true)));
+        }
+        if let Some(score) = fragment.score {
+            fields.push(("score", JsonWriter::to_json(&((score * 100.0).round() / 100.0))));
+        }
+    }
+
+    fn write_object(&mut self, fields: &[(&str, String)]) {
+        if self.written_fragment_count > 0 {
+            self.output.push_str(",\n");
+        }
+        self.output.push_str("  {\n");
+        let fields: Vec<String> = fields.iter().map(|(name, value)| format!("   \"{}\": {}", name, value)).collect();
+        self.output.push_str(&fields.join(",\n"));
+        self.output.push_str("\n  }");
+        self.written_fragment_count += 1;
+    }
+
+    // Field of the document (following the previous field)
+    pub fn field<T: Serialize + ?Sized>(name: &str, value: &T) -> String {
+        format!(",\n \"{}\": {}", name, JsonWriter::to_json(value))
+    }
+
+    // Field of the document with an array of objects (each object in a separate line)
+    pub fn array_field<T: Serialize>(name: &str, items: &[T]) -> String {
+        let items: Vec<String> = items.iter().map(|item| format!("\n  {}", JsonWriter::to_json(item))).collect();
+        let end = if items.is_empty() { "]" } else { "\n ]" };
+        format!(",\n \"{}\": [{}{}", name, items.join(","), end)
+    }
+
+    pub fn to_json<T: Serialize + ?Sized>(value: &T) -> String {
+        serde_json::to_string(value).expect("Extracted data can be serialized.")
+    }
+}
+
+struct OpenSpan {
+    kind: String,
+    href: Option<String>,
+    // Character offset of the span in the current fragment
+    start: Option<usize>,
 }
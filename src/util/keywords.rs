@@ -0,0 +1,150 @@
+use serde::Serialize;
+use std::collections::{HashMap, HashSet};
+
+use super::{analyzer::Analyzer, fragment::Fragment, json_writer::JsonWriter};
+use crate::text_extractor::ExtractParameters;
+
+// Stopwords of the languages separating the candidate keyphrases of a text
+const STOPWORDS: [(&str, &str); 10] = [
+    ("en", include_str!("stopwords/en.txt")),
+    ("de", include_str!("stopwords/de.txt")),
+    ("fr", include_str!("stopwords/fr.txt")),
+    ("es", include_str!("stopwords/es.txt")),
+    ("it", include_str!("stopwords/it.txt")),
+    ("pt", include_str!("stopwords/pt.txt")),
+    ("nl", include_str!("stopwords/nl.txt")),
+    ("hu", include_str!("stopwords/hu.txt")),
+    ("pl", include_str!("stopwords/pl.txt")),
+    ("ru", include_str!("stopwords/ru.txt")),
+];
+
+// Longer candidates are rarely keyphrases
+const MAX_PHRASE_WORDS: usize = 3;
+// Weights of the occurrences in the title and in headings
+const TITLE_WEIGHT: f64 = 3.0;
+const HEADING_WEIGHT: f64 = 2.0;
+
+#[derive(Serialize)]
+pub struct Keyword {
+    pub text: String,
+    pub score: f64,
+}
+
+// Extract the keywords and keyphrases of a document with RAKE (Rapid Automatic Keyword Extraction). Words of a phrase
+// are scored by the ratio of their degree (co-occurrences with other words of phrases) and their frequency, and
+// occurrences are weighted by the context of the text (like headings).
+#[derive(Default)]
+pub struct KeywordExtractor {
+    // Words of the text between punctuation marks and the weight of the text
+    segments: Vec<(Vec<String>, f64)>,
+}
+
+impl KeywordExtractor {
+    pub fn add(&mut self, text: &str, weight: f64) {
+        let is_delimiter = |ch: char| !(ch.is_alphanumeric() || ch.is_whitespace() || ch == '-' || ch == '\'' || ch == '’');
+        for segment in text.split(is_delimiter) {
+            let words: Vec<String> = segment
+                .split_whitespace()
+                .map(|word| word.trim_matches(|ch: char| !ch.is_alphanumeric()))
+                .filter(|word| !word.is_empty())
+                .map(String::from)
+                .collect();
+            if !words.is_empty() {
+                self.segments.push((words, weight));
+            }
+        }
+    }
+
+    // Return the best keywords using the stopwords of the language (or of all languages if it is not known)
+    pub fn keywords(&self, count: usize, language: Option<&str>) -> Vec<Keyword> {
+        let stopwords = KeywordExtractor::stopwords(language);
+
+        // Candidate phrases are the runs of words between stopwords (and numbers)
+        let mut phrases: HashMap<String, (String, f64)> = HashMap::new();
+        let mut words: HashMap<String, (f64, f64)> = HashMap::new();
+        for (segment, weight) in self.segments.iter() {
+            let is_separator = |word: &String| {
+                let lowercase = word.to_lowercase();
+                stopwords.contains(lowercase.as_str()) || word.chars().count() < 2 || word.chars().all(|ch| !ch.is_alphabetic())
+            };
+            for phrase in segment.split(is_separator).filter(|phrase| !phrase.is_empty() && phrase.len() <= MAX_PHRASE_WORDS) {
+                let key = phrase.join(" ").to_lowercase();
+                phrases.entry(key).or_insert_with(|| (phrase.join(" "), 0.0)).1 += weight;
+                for word in phrase {
+                    let (frequency, degree) = words.entry(word.to_lowercase()).or_default();
+                    *frequency += weight;
+                    *degree += weight * phrase.len() as f64;
+                }
+            }
+        }
+
+        let mut keywords: Vec<Keyword> = phrases
+            .into_iter()
+            .map(|(key, (text, occurrences))| {
+                let score: f64 = key.split(' ').filter_map(|word| words.get(word)).map(|(frequency, degree)| degree / frequency).sum();
+                Keyword { text, score: (score * occurrences * 100.0).round() / 100.0 }
+            })
+            .collect();
+        keywords.sort_by(|a, b| b.score.total_cmp(&a.score).then_with(|| a.text.cmp(&b.text)));
+        keywords.truncate(count);
+        keywords
+    }
+
+    // Stopwords of a language (or of all languages if it is not known)
+    pub fn stopwords(language: Option<&str>) -> HashSet<&'static str> {
+        let known = language.is_some_and(|language| STOPWORDS.iter().any(|(code, _)| *code == language));
+        STOPWORDS
+            .iter()
+            .filter(|(code, _)| !known || Some(*code) == language)
+            .flat_map(|(_, words)| words.split_whitespace())
+            .collect()
+    }
+}
+
+// Occurrences of keywords in the title and in headings are more important than in other texts
+impl Analyzer for KeywordExtractor {
+    fn add(&mut self, fragment: &Fragment) {
+        let weight = if fragment.title { TITLE_WEIGHT } else if fragment.heading_level.is_some() { HEADING_WEIGHT } else { 1.0 };
+        KeywordExtractor::add(self, &fragment.text, weight);
+    }
+
+    fn write_result(&mut self, parameters: &ExtractParameters, language: Option<&str>, output: &mut String) {
+        output.push_str(&JsonWriter::field("keywords", &self.keywords(parameters.keywords, language)));
+    }
+
+    fn needs_language(&self) -> bool {
+        true
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_keywords() {
+        let mut extractor = KeywordExtractor::default();
+        extractor.add("Solar panels", TITLE_WEIGHT);
+        extractor.add("Installing solar panels", HEADING_WEIGHT);
+        extractor.add("Solar panels convert sunlight into electricity. The installation of solar panels needs a sunny roof.", 1.0);
+        extractor.add("Batteries store the electricity.", 1.0);
+
+        // Phrases are scored by the degree and frequency of their words and by their weighted occurrences
+        let keywords: Vec<(String, f64)> = extractor.keywords(3, Some("en")).into_iter().map(|keyword| (keyword.text, keyword.score)).collect();
+        assert_eq!(keywords, vec![
+            (String::from("Solar panels"), 18.67),
+            (String::from("Installing solar panels"), 15.33),
+            (String::from("Batteries store"), 4.0)
+        ]);
+        assert!(KeywordExtractor::default().keywords(3, None).is_empty());
+    }
+
+    #[test]
+    fn test_stopwords() {
+        assert!(KeywordExtractor::stopwords(Some("en")).contains("the"));
+        assert!(!KeywordExtractor::stopwords(Some("en")).contains("der"));
+        // Stopwords of all languages are used if the language is not known
+        let stopwords = KeywordExtractor::stopwords(None);
+        assert!(stopwords.contains("the") && stopwords.contains("der"));
+    }
+}
@@ -0,0 +1,227 @@
+use serde::Serialize;
+use std::{collections::HashMap, sync::OnceLock};
+
+// Declared language of a text (from the lang attributes or the Content-Language header) and the detected one
+#[derive(Serialize, Default)]
+pub struct Language {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub declared: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub detected: Option<&'static str>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub confidence: Option<f64>,
+}
+
+impl Language {
+    pub fn new(declared: Option<String>, text: &str) -> Self {
+        let detected = LanguageDetector::detect(text);
+        Self {
+            declared,
+            detected: detected.map(|(language, _)| language),
+            confidence: detected.map(|(_, confidence)| (confidence * 100.0).round() / 100.0),
+        }
+    }
+}
+
+#[derive(PartialEq, Clone, Copy)]
+enum Script { Latin, Cyrillic, Greek, Arabic, Hebrew, Han, Kana, Hangul, Thai, Devanagari }
+
+// Sample texts of the languages written with Latin or Cyrillic letters. Languages are detected by the probability of the
+// trigrams of a text in the samples.
+const SAMPLES: [(&str, Script, &str); 11] = [
+    ("en", Script::Latin, include_str!("languages/en.txt")),
+    ("de", Script::Latin, include_str!("languages/de.txt")),
+    ("fr", Script::Latin, include_str!("languages/fr.txt")),
+    ("es", Script::Latin, include_str!("languages/es.txt")),
+    ("it", Script::Latin, include_str!("languages/it.txt")),
+    ("pt", Script::Latin, include_str!("languages/pt.txt")),
+    ("nl", Script::Latin, include_str!("languages/nl.txt")),
+    ("hu", Script::Latin, include_str!("languages/hu.txt")),
+    ("pl", Script::Latin, include_str!("languages/pl.txt")),
+    ("ru", Script::Cyrillic, include_str!("languages/ru.txt")),
+    ("uk", Script::Cyrillic, include_str!("languages/uk.txt")),
+];
+
+// Estimated number of the trigrams of a language, used to give a probability to the trigrams missing from its sample
+const TRIGRAM_COUNT: f64 = 20000.0;
+// Texts with less letters are too short to detect their language. Languages written with Latin or Cyrillic letters are
+// told apart by their trigrams, which need longer texts (a few words are often equally probable in several languages).
+const MIN_LETTERS: usize = 10;
+const MIN_TRIGRAM_LETTERS: usize = 20;
+
+struct LanguageModel {
+    language: &'static str,
+    script: Script,
+    // Logarithm of the probability of the trigrams in the sample and of the trigrams not found in it
+    trigrams: HashMap<String, f64>,
+    unknown: f64,
+}
+
+pub struct LanguageDetector {}
+
+impl LanguageDetector {
+    // Detect the language of a text and return its code with a confidence between 0 and 1. Confidence of the languages
+    // detected by trigrams grows with the difference of the trigram probabilities in the best and in the second language
+    // (it does not depend on the length of the text). Confidence is reduced by the share of the letters of other scripts.
+    pub fn detect(text: &str) -> Option<(&'static str, f64)> {
+        let mut scripts: Vec<(Script, usize)> = Vec::new();
+        for script in text.chars().filter_map(LanguageDetector::script) {
+            match scripts.iter_mut().find(|(known, _)| *known == script) {
+                Some((_, count)) => *count += 1,
+                None => scripts.push((script, 1)),
+            }
+        }
+        let letters: usize = scripts.iter().map(|(_, count)| count).sum();
+        let (script, count) = scripts.iter().copied().max_by_key(|(_, count)| *count)?;
+        if letters < MIN_LETTERS {
+            return None;
+        }
+        let share = count as f64 / letters as f64;
+
+        // Japanese texts contain both Kana and Han characters
+        let kana = scripts.iter().find(|(known, _)| *known == Script::Kana).map_or(0, |(_, count)| *count);
+        let han = scripts.iter().find(|(known, _)| *known == Script::Han).map_or(0, |(_, count)| *count);
+        let language = match script {
+            Script::Latin | Script::Cyrillic if letters < MIN_TRIGRAM_LETTERS => return None,
+            Script::Latin | Script::Cyrillic => {
+                return LanguageDetector::compare_trigrams(text, script).map(|(language, confidence)| (language, confidence * share));
+            }
+            Script::Han | Script::Kana if kana > 0 => return Some(("ja", (kana + han) as f64 / letters as f64)),
+            Script::Han | Script::Kana => "zh",
+            Script::Greek => "el",
+            Script::Arabic => "ar",
+            Script::Hebrew => "he",
+            Script::Hangul => "ko",
+            Script::Thai => "th",
+            Script::Devanagari => "hi",
+        };
+        Some((language, share))
+    }
+
+    fn script(ch: char) -> Option<Script> {
+        if !ch.is_alphabetic() {
+            return None;
+        }
+        match ch {
+            'a'..='z' | 'A'..='Z' | '\u{00C0}'..='\u{024F}' | '\u{1E00}'..='\u{1EFF}' => Some(Script::Latin),
+            '\u{0370}'..='\u{03FF}' => Some(Script::Greek),
+            '\u{0400}'..='\u{04FF}' => Some(Script::Cyrillic),
+            '\u{0590}'..='\u{05FF}' => Some(Script::Hebrew),
+            '\u{0600}'..='\u{06FF}' => Some(Script::Arabic),
+            '\u{0900}'..='\u{097F}' => Some(Script::Devanagari),
+            '\u{0E00}'..='\u{0E7F}' => Some(Script::Thai),
+            '\u{1100}'..='\u{11FF}' | '\u{AC00}'..='\u{D7AF}' => Some(Script::Hangul),
+            '\u{3040}'..='\u{30FF}' => Some(Script::Kana),
+            '\u{3400}'..='\u{4DBF}' | '\u{4E00}'..='\u{9FFF}' => Some(Script::Han),
+            _ => None,
+        }
+    }
+
+    // Find the language model giving the highest probability to the trigrams of the text
+    fn compare_trigrams(text: &str, script: Script) -> Option<(&'static str, f64)> {
+        let trigrams = LanguageDetector::trigrams(text);
+        let count: f64 = trigrams.values().sum();
+        if count == 0.0 {
+            return None;
+        }
+
+        let mut scores: Vec<(&'static str, f64)> = LanguageDetector::models()
+            .iter()
+            .filter(|model| model.script == script)
+            .map(|model| {
+                let score = trigrams.iter().map(|(trigram, count)| count * model.trigrams.get(trigram).copied().unwrap_or(model.unknown)).sum();
+                (model.language, score)
+            })
+            .collect();
+        scores.sort_by(|a, b| b.1.total_cmp(&a.1));
+
+        // Confidence is low if the trigrams are almost as probable in another language
+        let (language, best) = *scores.first()?;
+        let second = scores.get(1).map_or(f64::NEG_INFINITY, |(_, score)| *score);
+        Some((language, 1.0 - (-(best - second) / count).exp()))
+    }
+
+    // Count the trigrams of the words of a text (words are padded with a space on both sides)
+    fn trigrams(text: &str) -> HashMap<String, f64> {
+        let mut trigrams = HashMap::new();
+        for word in text.split(|ch: char| !ch.is_alphabetic()).filter(|word| !word.is_empty()) {
+            let chars: Vec<char> = std::iter::once(' ').chain(word.chars().flat_map(char::to_lowercase)).chain(std::iter::once(' ')).collect();
+            for trigram in chars.windows(3) {
+                *trigrams.entry(trigram.iter().collect()).or_insert(0.0) += 1.0;
+            }
+        }
+        trigrams
+    }
+
+    // Models are built from the samples when they are used first
+    fn models() -> &'static Vec<LanguageModel> {
+        static MODELS: OnceLock<Vec<LanguageModel>> = OnceLock::new();
+        MODELS.get_or_init(|| {
+            SAMPLES
+                .iter()
+                .map(|(language, script, sample)| {
+                    // Probabilities are smoothed by adding a half occurrence to each trigram
+                    let trigrams = LanguageDetector::trigrams(sample);
+                    let total = trigrams.values().sum::<f64>() + TRIGRAM_COUNT / 2.0;
+                    let trigrams = trigrams.into_iter().map(|(trigram, count)| (trigram, ((count + 0.5) / total).ln())).collect();
+                    LanguageModel { language, script: *script, trigrams, unknown: (0.5 / total).ln() }
+                })
+                .collect()
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_detect() {
+        // Sentences not contained in the samples are detected on their own
+        let sentences = [
+            ("en", "Where did you put the keys of the car?"), ("en", "She has lived in this town since she was a child."),
+            ("en", "Please send me the report before the meeting starts."),
+            ("de", "Wo hast du die Schlüssel für das Auto hingelegt?"), ("de", "Sie wohnt seit ihrer Kindheit in dieser Stadt."),
+            ("de", "Bitte schicke mir den Bericht vor dem Beginn der Besprechung."),
+            ("fr", "Où as-tu mis les clés de la voiture?"), ("fr", "Elle habite dans cette ville depuis son enfance."),
+            ("fr", "Envoie-moi le rapport avant le début de la réunion, s'il te plaît."),
+            ("es", "¿Dónde pusiste las llaves del coche?"), ("es", "Ella vive en esta ciudad desde que era una niña."),
+            ("es", "Por favor, envíame el informe antes de que empiece la reunión."),
+            ("it", "Dove hai messo le chiavi della macchina?"), ("it", "Lei abita in questa città da quando era bambina."),
+            ("it", "Per favore, mandami la relazione prima che inizi la riunione."),
+            ("pt", "Onde você colocou as chaves do carro?"), ("pt", "Ela mora nesta cidade desde que era criança."),
+            ("pt", "Por favor, envie-me o relatório antes do início da reunião."),
+            ("nl", "Waar heb je de sleutels van de auto gelegd?"), ("nl", "Zij woont al sinds haar jeugd in deze stad."),
+            ("nl", "Stuur me alsjeblieft het verslag voordat de vergadering begint."),
+            ("hu", "Hová tetted az autó kulcsait?"), ("hu", "Gyerekkora óta ebben a városban lakik."),
+            ("hu", "Kérlek, küldd el a jelentést a megbeszélés kezdete előtt."),
+            ("pl", "Gdzie położyłeś klucze do samochodu?"), ("pl", "Ona mieszka w tym mieście od dzieciństwa."),
+            ("pl", "Proszę, wyślij mi raport przed rozpoczęciem spotkania."),
+            ("ru", "Куда ты положил ключи от машины?"), ("ru", "Она живёт в этом городе с самого детства."),
+            ("ru", "Пожалуйста, пришли мне отчёт до начала совещания."),
+            ("uk", "Куди ти поклав ключі від машини?"), ("uk", "Вона живе в цьому місті з самого дитинства."),
+            ("uk", "Будь ласка, надішли мені звіт до початку наради."),
+        ];
+        for (language, sentence) in sentences {
+            let detected = LanguageDetector::detect(sentence);
+            assert_eq!(detected.map(|(language, _)| language), Some(language), "{}", sentence);
+            assert!(detected.is_some_and(|(_, confidence)| confidence > 0.0 && confidence <= 1.0), "{}", sentence);
+        }
+    }
+
+    #[test]
+    fn test_scripts() {
+        // Texts too short to tell the languages apart are not detected
+        assert_eq!(LanguageDetector::detect("Hi"), None);
+        assert_eq!(LanguageDetector::detect("Wo ist der Bahnhof?"), None);
+        assert_eq!(LanguageDetector::detect("Wo ist der Bahnhof, bitte?").map(|(language, _)| language), Some("de"));
+        assert_eq!(LanguageDetector::detect("1234567890 !!!"), None);
+        assert_eq!(LanguageDetector::detect("今日はとても良い天気ですね").map(|(language, _)| language), Some("ja"));
+        assert_eq!(LanguageDetector::detect("今天天气很好我们去公园散步"), Some(("zh", 1.0)));
+        assert_eq!(LanguageDetector::detect("Καλημέρα σε όλους τους φίλους").map(|(language, _)| language), Some("el"));
+        assert_eq!(LanguageDetector::detect("안녕하세요 만나서 반갑습니다").map(|(language, _)| language), Some("ko"));
+
+        let language = Language::new(Some(String::from("en")), "Das Wetter wird für den Rest der Woche warm und sonnig sein.");
+        assert_eq!((language.declared.as_deref(), language.detected), (Some("en"), Some("de")));
+    }
+}
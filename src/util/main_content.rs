@@ -0,0 +1,142 @@
+use super::fragment::Fragment;
+
+// Words in class and id attributes indicating the main content of the page
+const POSITIVE_HINTS: [&str; 9] = ["article", "blog", "content", "entry", "main", "post", "story", "text", "body"];
+
+// Words in class and id attributes indicating navigation, advertisements and other boilerplate
+const NEGATIVE_HINTS: [&str; 28] = [
+    "ad", "ads", "advert", "banner", "breadcrumb", "breadcrumbs", "comment", "comments", "consent", "cookie", "cookies", "footer",
+    "header", "masthead", "menu", "modal", "nav", "navbar", "newsletter", "popup", "promo", "related", "share", "sidebar", "social",
+    "sponsor", "subscribe", "widget",
+];
+
+const HINT_WEIGHT: i32 = 25;
+
+// Detects the main content of a document by scoring its fragments in the spirit of Readability and boilerpipe
+pub struct MainContent {}
+
+impl MainContent {
+
+    // Score of an element based on its name and the words of its class and id attributes
+    pub fn element_hint<'a>(element_name: &str, names: impl Iterator<Item = &'a str>) -> i32 {
+        let mut hint = match element_name {
+            "main" | "article" => HINT_WEIGHT,
+            "nav" | "aside" | "footer" | "header" | "form" | "menu" => -HINT_WEIGHT,
+            _ => 0,
+        };
+
+        let words: Vec<String> = names
+            .flat_map(|name| name.split(|ch: char| !ch.is_alphanumeric()))
+            .map(|word| word.to_lowercase())
+            .collect();
+        if words.iter().any(|word| POSITIVE_HINTS.contains(&word.as_str())) {
+            hint += HINT_WEIGHT;
+        }
+        if words.iter().any(|word| NEGATIVE_HINTS.contains(&word.as_str())) {
+            hint -= HINT_WEIGHT;
+        }
+
+        hint
+    }
+
+    // Score of a fragment. Long texts with few links are likely part of the main content
+    pub fn score(fragment: &Fragment) -> f64 {
+        let words = fragment.text.split_whitespace().count() as f64;
+        let length = fragment.text.chars().count().max(1) as f64;
+        let link_density = (fragment.link_length as f64 / length).min(1.0);
+
+        let mut score = words * (1.0 - link_density) - 10.0 + f64::from(fragment.hint);
+        if link_density > 0.5 {
+            score -= words * link_density;
+        }
+        score
+    }
+
+    // Keep the fragments of the main content only. The main content is the continuous range of fragments having the
+    // highest total score. (Short fragments like headings can be part of it when they are surrounded by long ones.)
+    pub fn select(mut fragments: Vec<Fragment>) -> Vec<Fragment> {
+        for fragment in fragments.iter_mut() {
+            fragment.score = Some(MainContent::score(fragment));
+        }
+
+        let scores: Vec<f64> = fragments.iter().map(|fragment| fragment.score.unwrap_or_default()).collect();
+        let (start, end) = MainContent::best_range(&scores);
+        fragments
+            .into_iter()
+            .take(end)
+            .skip(start)
+            .filter(|fragment| !MainContent::is_link_list(fragment))
+            .collect()
+    }
+
+    // Start and end index of the continuous range of fragments having the highest total score
+    pub fn best_range(scores: &[f64]) -> (usize, usize) {
+        let mut best = (f64::MIN, 0, 0);
+        let mut sum = 0.0;
+        let mut start = 0;
+        for (index, score) in scores.iter().enumerate() {
+            if sum <= 0.0 {
+                sum = 0.0;
+                start = index;
+            }
+            sum += score;
+            if sum > best.0 {
+                best = (sum, start, index + 1);
+            }
+        }
+        (best.1, best.2)
+    }
+
+    // Link lists inside of the main content (like 'Read more' sections) are removed as well
+    pub fn is_link_list(fragment: &Fragment) -> bool {
+        let length = fragment.text.chars().count().max(1);
+        fragment.link_length * 2 > length
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn fragment(text: &str, link_length: usize, hint: i32) -> Fragment {
+        Fragment { text: String::from(text), link_length, hint, ..Default::default() }
+    }
+
+    #[test]
+    fn test_element_hint() {
+        assert_eq!(MainContent::element_hint("article", ["post-body"].into_iter()), 50);
+        assert_eq!(MainContent::element_hint("div", ["cookie-banner"].into_iter()), -25);
+        assert_eq!(MainContent::element_hint("nav", ["main-menu"].into_iter()), -25);
+        assert_eq!(MainContent::element_hint("div", ["Headline", "adjacent"].into_iter()), 0);
+    }
+
+    #[test]
+    fn test_score() {
+        let text = "The first paragraph of the article contains enough words to be recognised as the main content.";
+        assert_eq!(MainContent::score(&fragment(text, 0, 0)), 6.0);
+        assert_eq!(MainContent::score(&fragment(text, 0, 25)), 31.0);
+        assert!(MainContent::score(&fragment("Home News About", 15, 0)) < -10.0);
+        assert!(MainContent::is_link_list(&fragment("Related story one", 17, 0)));
+        assert!(!MainContent::is_link_list(&fragment("A story with a link", 4, 0)));
+    }
+
+    #[test]
+    fn test_best_range() {
+        assert_eq!(MainContent::best_range(&[-5.0, 10.0, -2.0, 8.0, -20.0, 3.0]), (1, 4));
+        assert_eq!(MainContent::best_range(&[-5.0, -1.0, -3.0]), (1, 2));
+        assert_eq!(MainContent::best_range(&[]), (0, 0));
+
+        // Short fragments surrounded by the main content are kept, link lists inside it are removed
+        let text = "The paragraph of the article contains enough words to be recognised as the main content of the page.";
+        let fragments = vec![
+            fragment("Home News About", 15, -25),
+            fragment("Title", 0, 25),
+            fragment(text, 0, 25),
+            fragment("Related story one", 17, 25),
+            fragment(text, 0, 25),
+            fragment("Copyright 2024 Example Publishing. All rights reserved.", 0, -25),
+        ];
+        let selected: Vec<String> = MainContent::select(fragments).into_iter().map(|fragment| fragment.text).collect();
+        assert_eq!(selected, vec!["Title", text, text]);
+    }
+}
@@ -1,3 +1,19 @@
+pub mod analyzer;
+pub mod chunker;
+pub mod entities;
+pub mod fingerprint;
+pub mod format_detector;
+pub mod fragment;
 pub mod json_writer;
+pub mod keywords;
+pub mod language;
+pub mod main_content;
+pub mod normalizer;
+pub mod redactor;
+pub mod section_tree;
+pub mod sentences;
+pub mod stats;
+pub mod summarizer;
+pub mod table;
 pub mod utf8_reader;
 pub mod utf8_writer;
\ No newline at end of file
@@ -0,0 +1,164 @@
+use unicode_normalization::{is_nfc, is_nfkc, UnicodeNormalization};
+use unicode_segmentation::UnicodeSegmentation;
+
+use super::fragment::Fragment;
+use crate::text_extractor::{ExtractParameters, Normalization};
+
+// Result of cleaning a character of the extracted text
+pub enum Cleaned {
+    Removed,
+    Char(char),
+    Text(&'static str),
+}
+
+// Clean the characters written to the output and normalize the Unicode form of the fragments
+pub struct TextNormalizer {
+    normalization: Normalization,
+    remove_invisible: bool,
+    fold_punctuation: bool,
+    expand_ligatures: bool,
+}
+
+impl TextNormalizer {
+    pub fn new(parameters: &ExtractParameters) -> Self {
+        Self {
+            normalization: parameters.normalization,
+            remove_invisible: parameters.remove_invisible,
+            fold_punctuation: parameters.fold_punctuation,
+            expand_ligatures: parameters.expand_ligatures,
+        }
+    }
+
+    #[inline]
+    pub fn clean(&self, ch: char) -> Cleaned {
+        if ch.is_ascii() {
+            return Cleaned::Char(ch);
+        }
+        if self.remove_invisible {
+            match ch {
+                // Soft hyphen, zero width characters, word joiners, byte order mark and bidirectional marks
+                '\u{00AD}' | '\u{061C}' | '\u{180E}' | '\u{200B}'..='\u{200F}' | '\u{202A}'..='\u{202E}' | '\u{2060}'..='\u{2064}'
+                | '\u{2066}'..='\u{2069}' | '\u{FEFF}' => return Cleaned::Removed,
+                // Non-breaking and fixed width spaces
+                '\u{00A0}' | '\u{2000}'..='\u{200A}' | '\u{202F}' | '\u{205F}' | '\u{3000}' => return Cleaned::Char(' '),
+                _ => {}
+            }
+        }
+        if self.fold_punctuation {
+            match ch {
+                '‘' | '’' | '‚' | '‛' | '′' | '‹' | '›' => return Cleaned::Char('\''),
+                '“' | '”' | '„' | '‟' | '″' | '«' | '»' => return Cleaned::Char('"'),
+                '\u{2010}'..='\u{2015}' | '\u{2212}' | '\u{FE58}' | '\u{FE63}' | '\u{FF0D}' => return Cleaned::Char('-'),
+                _ => {}
+            }
+        }
+        if self.expand_ligatures {
+            match ch {
+                'ﬀ' => return Cleaned::Text("ff"),
+                'ﬁ' => return Cleaned::Text("fi"),
+                'ﬂ' => return Cleaned::Text("fl"),
+                'ﬃ' => return Cleaned::Text("ffi"),
+                'ﬄ' => return Cleaned::Text("ffl"),
+                'ﬅ' | 'ﬆ' => return Cleaned::Text("st"),
+                'Ĳ' => return Cleaned::Text("IJ"),
+                'ĳ' => return Cleaned::Text("ij"),
+                'Ǆ' => return Cleaned::Text("DŽ"),
+                'ǅ' => return Cleaned::Text("Dž"),
+                'ǆ' => return Cleaned::Text("dž"),
+                'Ǉ' => return Cleaned::Text("LJ"),
+                'ǈ' => return Cleaned::Text("Lj"),
+                'ǉ' => return Cleaned::Text("lj"),
+                'Ǌ' => return Cleaned::Text("NJ"),
+                'ǋ' => return Cleaned::Text("Nj"),
+                'ǌ' => return Cleaned::Text("nj"),
+                _ => {}
+            }
+        }
+        Cleaned::Char(ch)
+    }
+
+    // Normalize the text of a completed fragment and move its spans. Grapheme clusters are normalized one by one, so
+    // span offsets can be mapped from the old text to the new one (a base character is composed with its marks only).
+    pub fn normalize(&self, fragment: &mut Fragment) {
+        let is_normalized = match self.normalization {
+            Normalization::None => true,
+            Normalization::Nfc => is_nfc(&fragment.text),
+            Normalization::Nfkc => is_nfkc(&fragment.text),
+        };
+        if is_normalized {
+            return;
+        }
+
+        let mut text = String::with_capacity(fragment.text.len());
+        let mut offsets = Vec::new();
+        let mut length = 0;
+        for grapheme in fragment.text.graphemes(true) {
+            offsets.extend(grapheme.chars().map(|_| length));
+            let normalized: String = match self.normalization {
+                Normalization::Nfkc => grapheme.nfkc().collect(),
+                _ => grapheme.nfc().collect(),
+            };
+            length += normalized.chars().count();
+            text.push_str(&normalized);
+        }
+        offsets.push(length);
+
+        for span in fragment.spans.iter_mut() {
+            span.start = offsets.get(span.start).copied().unwrap_or(length);
+            span.end = offsets.get(span.end).copied().unwrap_or(length);
+        }
+        fragment.text = text;
+        fragment.rewritten = true;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::util::fragment::Span;
+
+    fn clean(normalizer: &TextNormalizer, text: &str) -> String {
+        let mut cleaned = String::new();
+        for ch in text.chars() {
+            match normalizer.clean(ch) {
+                Cleaned::Char(ch) => cleaned.push(ch),
+                Cleaned::Text(text) => cleaned.push_str(text),
+                Cleaned::Removed => {}
+            }
+        }
+        cleaned
+    }
+
+    #[test]
+    fn test_clean() {
+        let text = "co\u{AD}op\u{200D}eration “quoted” – \u{FB01}ne\u{202F}print";
+        assert_eq!(clean(&TextNormalizer::new(&ExtractParameters::default()), text), text);
+
+        let parameters = ExtractParameters { remove_invisible: true, fold_punctuation: true, expand_ligatures: true, ..Default::default() };
+        assert_eq!(clean(&TextNormalizer::new(&parameters), text), "cooperation \"quoted\" - fine print");
+    }
+
+    #[test]
+    fn test_normalize() {
+        let mut fragment = Fragment {
+            text: String::from("Cafe\u{301} nai\u{308}ve"),
+            spans: vec![Span { kind: String::from("strong"), start: 6, end: 12, href: None }],
+            ..Default::default()
+        };
+        TextNormalizer::new(&ExtractParameters::default()).normalize(&mut fragment);
+        assert_eq!(fragment.text, "Cafe\u{301} nai\u{308}ve");
+        assert!(!fragment.rewritten);
+
+        // Spans are moved to the offsets of the composed characters
+        let parameters = ExtractParameters { normalization: Normalization::Nfc, ..Default::default() };
+        TextNormalizer::new(&parameters).normalize(&mut fragment);
+        assert_eq!(fragment.text, "Café naïve");
+        assert_eq!((fragment.spans[0].start, fragment.spans[0].end), (5, 10));
+        assert!(fragment.rewritten);
+
+        let mut fragment = Fragment { text: String::from("x\u{B2} \u{FB01}le"), ..Default::default() };
+        let parameters = ExtractParameters { normalization: Normalization::Nfkc, ..Default::default() };
+        TextNormalizer::new(&parameters).normalize(&mut fragment);
+        assert_eq!(fragment.text, "x2 file");
+    }
+}
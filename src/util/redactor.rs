@@ -0,0 +1,221 @@
+use regex::Regex;
+use serde::Serialize;
+use std::sync::OnceLock;
+
+use super::fragment::Fragment;
+
+// Patterns shared with the entity extractor
+pub const EMAIL_PATTERN: &str = r"[A-Za-z0-9._%+-]+@[A-Za-z0-9-]+(?:\.[A-Za-z0-9-]+)*\.[A-Za-z]{2,}";
+// Phone numbers start with an international prefix or an area code in parentheses, or their digits are grouped by
+// separators (so other large numbers are not matched)
+pub const PHONE_PATTERN: &str = r"\+\d{1,3}[ .-]?(?:\(\d{1,4}\)[ .-]?)?\d{1,4}(?:[ .-]?\d{2,4}){1,5}\b|\(\d{1,4}\)[ .-]?\d{2,4}(?:[ .-]?\d{2,4}){1,4}\b|\b\d{1,4}(?:[ .-]\d{2,4}){2,5}\b";
+
+#[derive(Clone, Copy, PartialEq)]
+enum PiiType { Email, Iban, CreditCard, IpAddress, NationalId, Phone }
+
+// Patterns of personal data in the order they are searched (matches overlapping an earlier match are ignored)
+const PATTERNS: [(PiiType, &str); 6] = [
+    (PiiType::Email, EMAIL_PATTERN),
+    (PiiType::Iban, r"\b[A-Z]{2}\d{2}(?: ?[A-Z0-9]{4}){2,7}(?: ?[A-Z0-9]{1,3})?\b"),
+    (PiiType::CreditCard, r"\b\d(?:[ -]?\d){12,18}\b"),
+    (PiiType::IpAddress, r"(?i)\b(?:(?:25[0-5]|2[0-4]\d|1\d\d|[1-9]?\d)\.){3}(?:25[0-5]|2[0-4]\d|1\d\d|[1-9]?\d)\b|\b(?:[0-9a-f]{1,4}:){7}[0-9a-f]{1,4}\b|\b(?:[0-9a-f]{1,4}:){1,6}:(?:[0-9a-f]{1,4}(?::[0-9a-f]{1,4}){0,5})?"),
+    // US social security numbers and UK national insurance numbers
+    (PiiType::NationalId, r"\b\d{3}-\d{2}-\d{4}\b|\b[A-CEGHJ-PR-TW-Z]{2} ?\d{2} ?\d{2} ?\d{2} ?[A-D]\b"),
+    (PiiType::Phone, PHONE_PATTERN),
+];
+
+// Number of redacted texts by type
+#[derive(Serialize, Default)]
+pub struct RedactionCounts {
+    pub email: usize,
+    pub phone: usize,
+    pub iban: usize,
+    pub credit_card: usize,
+    pub ip_address: usize,
+    pub national_id: usize,
+}
+
+// Replace personal data in the text of the fragments with placeholders of their type (like '[EMAIL]')
+#[derive(Default)]
+pub struct Redactor {
+    pub counts: RedactionCounts,
+}
+
+impl Redactor {
+    pub fn redact(&mut self, fragment: &mut Fragment) {
+        for (start, end, pii_type) in self.find(&fragment.text) {
+            let (char_start, char_end) = (fragment.text[..start].chars().count(), fragment.text[..end].chars().count());
+            fragment.replace_range(char_start, char_end, Redactor::placeholder(pii_type));
+        }
+
+        // Links to e-mail addresses and phone numbers are removed, personal data in the other links is replaced
+        for span in fragment.spans.iter_mut() {
+            let Some(href) = span.href.as_mut() else {
+                continue;
+            };
+            let scheme = href.split(':').next().unwrap_or("").to_lowercase();
+            if scheme == "mailto" || scheme == "tel" {
+                *self.count(if scheme == "mailto" { PiiType::Email } else { PiiType::Phone }) += 1;
+                span.href = None;
+                continue;
+            }
+            for (start, end, pii_type) in self.find(href) {
+                href.replace_range(start..end, Redactor::placeholder(pii_type));
+            }
+        }
+    }
+
+    // Replace personal data in a text which is not part of a fragment (like the fields of feed items)
+    pub fn redact_text(&mut self, text: &str) -> String {
+        let mut text = String::from(text);
+        for (start, end, pii_type) in self.find(&text) {
+            text.replace_range(start..end, Redactor::placeholder(pii_type));
+        }
+        text
+    }
+
+    // Find the personal data in a text and count it. Matches are returned from the end of the text, so the byte offsets
+    // of the other matches remain valid when a match is replaced.
+    fn find(&mut self, text: &str) -> Vec<(usize, usize, PiiType)> {
+        let mut matches: Vec<(usize, usize, PiiType)> = Vec::new();
+        for (pii_type, regex) in Redactor::patterns() {
+            for found in regex.find_iter(text) {
+                let overlaps = matches.iter().any(|(start, end, _)| found.start() < *end && found.end() > *start);
+                if !overlaps && Redactor::is_valid(*pii_type, found.as_str()) {
+                    matches.push((found.start(), found.end(), *pii_type));
+                }
+            }
+        }
+        matches.sort_by_key(|(start, _, _)| std::cmp::Reverse(*start));
+        for (_, _, pii_type) in matches.iter() {
+            *self.count(*pii_type) += 1;
+        }
+        matches
+    }
+
+    fn placeholder(pii_type: PiiType) -> &'static str {
+        match pii_type {
+            PiiType::Email => "[EMAIL]",
+            PiiType::Iban => "[IBAN]",
+            PiiType::CreditCard => "[CREDIT_CARD]",
+            PiiType::IpAddress => "[IP_ADDRESS]",
+            PiiType::NationalId => "[NATIONAL_ID]",
+            PiiType::Phone => "[PHONE]",
+        }
+    }
+
+    fn count(&mut self, pii_type: PiiType) -> &mut usize {
+        match pii_type {
+            PiiType::Email => &mut self.counts.email,
+            PiiType::Iban => &mut self.counts.iban,
+            PiiType::CreditCard => &mut self.counts.credit_card,
+            PiiType::IpAddress => &mut self.counts.ip_address,
+            PiiType::NationalId => &mut self.counts.national_id,
+            PiiType::Phone => &mut self.counts.phone,
+        }
+    }
+
+    // Check the digits of the numbers to avoid redacting other numbers (like prices or dates)
+    fn is_valid(pii_type: PiiType, text: &str) -> bool {
+        let digits: Vec<u32> = text.chars().filter_map(|ch| ch.to_digit(10)).collect();
+        match pii_type {
+            PiiType::Iban => Redactor::is_valid_iban(text),
+            PiiType::CreditCard => (13..=19).contains(&digits.len()) && Redactor::luhn(&digits),
+            PiiType::NationalId if text.contains('-') => !text.starts_with("000") && !text.starts_with("666") && !text.starts_with('9'),
+            PiiType::Phone => Redactor::is_phone_number(text),
+            _ => true,
+        }
+    }
+
+    // Phone numbers are written with an international prefix or contain at least 9 digits
+    pub fn is_phone_number(text: &str) -> bool {
+        let digit_count = text.chars().filter(char::is_ascii_digit).count();
+        (7..=15).contains(&digit_count) && (text.starts_with('+') || digit_count >= 9)
+    }
+
+    // Luhn checksum of credit card numbers
+    fn luhn(digits: &[u32]) -> bool {
+        let sum: u32 = digits
+            .iter()
+            .rev()
+            .enumerate()
+            .map(|(index, digit)| if index % 2 == 1 { if digit * 2 > 9 { digit * 2 - 9 } else { digit * 2 } } else { *digit })
+            .sum();
+        sum.is_multiple_of(10)
+    }
+
+    // IBANs are valid if the number formed by moving the country code and check digits to the end is 1 modulo 97
+    fn is_valid_iban(text: &str) -> bool {
+        let iban: Vec<char> = text.chars().filter(|ch| !ch.is_whitespace()).collect();
+        if !(15..=34).contains(&iban.len()) {
+            return false;
+        }
+        let mut remainder = 0;
+        for ch in iban[4..].iter().chain(iban[..4].iter()) {
+            let Some(value) = ch.to_digit(36) else {
+                return false;
+            };
+            remainder = if value < 10 { (remainder * 10 + value) % 97 } else { (remainder * 100 + value) % 97 };
+        }
+        remainder == 1
+    }
+
+    fn patterns() -> &'static Vec<(PiiType, Regex)> {
+        static REGEXES: OnceLock<Vec<(PiiType, Regex)>> = OnceLock::new();
+        REGEXES.get_or_init(|| PATTERNS.iter().map(|(pii_type, pattern)| (*pii_type, Regex::new(pattern).expect("Patterns are valid."))).collect())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::util::fragment::Span;
+
+    #[test]
+    fn test_redact_text() {
+        let mut redactor = Redactor::default();
+        assert_eq!(redactor.redact_text("Write to jane.doe@example.com or call +36 30 123 4567."), "Write to [EMAIL] or call [PHONE].");
+        assert_eq!(redactor.redact_text("IBAN: DE89 3704 0044 0532 0130 00, card 4111 1111 1111 1111, invalid card 4111 1111 1111 1112."),
+            "IBAN: [IBAN], card [CREDIT_CARD], invalid card 4111 1111 1111 1112.");
+        assert_eq!(redactor.redact_text("Server 192.168.0.1 and fe80::1ff:fe23:4567:890a, SSN 123-45-6789, NINO AB 12 34 56 C."),
+            "Server [IP_ADDRESS] and [IP_ADDRESS], SSN [NATIONAL_ID], NINO [NATIONAL_ID].");
+        // Prices, dates and other numbers are kept
+        assert_eq!(redactor.redact_text("Price: 1 200 EUR on 2024-01-15, about 330000000 people."),
+            "Price: 1 200 EUR on 2024-01-15, about 330000000 people.");
+        assert_eq!(serde_json::to_value(&redactor.counts).unwrap(), serde_json::json!({
+            "email": 1, "phone": 1, "iban": 1, "credit_card": 1, "ip_address": 2, "national_id": 2
+        }));
+    }
+
+    #[test]
+    fn test_redact_fragment() {
+        let span = |start: usize, end: usize, href: Option<&str>| Span { kind: String::from("a"), start, end, href: href.map(String::from) };
+        let mut fragment = Fragment {
+            text: String::from("Mail jane.doe@example.com or visit the profile."),
+            spans: vec![span(5, 25, Some("mailto:jane.doe@example.com")), span(35, 46, Some("/profile?email=jane.doe@example.com"))],
+            ..Default::default()
+        };
+        let mut redactor = Redactor::default();
+        redactor.redact(&mut fragment);
+
+        // Spans after the replaced text are moved, links to e-mail addresses are removed and personal data is replaced in the other links
+        assert_eq!(fragment.text, "Mail [EMAIL] or visit the profile.");
+        let spans: Vec<(usize, usize, Option<&str>)> = fragment.spans.iter().map(|span| (span.start, span.end, span.href.as_deref())).collect();
+        assert_eq!(spans, vec![(5, 12, None), (22, 33, Some("/profile?email=[EMAIL]"))]);
+        assert_eq!(redactor.counts.email, 3);
+    }
+
+    #[test]
+    fn test_checksums() {
+        let digits = |text: &str| text.chars().filter_map(|ch| ch.to_digit(10)).collect::<Vec<u32>>();
+        assert!(Redactor::luhn(&digits("4111 1111 1111 1111")));
+        assert!(Redactor::luhn(&digits("79927398713")));
+        assert!(!Redactor::luhn(&digits("4111 1111 1111 1112")));
+        assert!(Redactor::is_valid_iban("DE89 3704 0044 0532 0130 00"));
+        assert!(Redactor::is_valid_iban("GB82WEST12345698765432"));
+        assert!(!Redactor::is_valid_iban("DE88 3704 0044 0532 0130 00"));
+        assert!(!Redactor::is_valid_iban("DE89 3704"));
+        assert!(Redactor::is_phone_number("+36 30 123 4567"));
+        assert!(!Redactor::is_phone_number("12 34 56"));
+    }
+}
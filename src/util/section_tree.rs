@@ -0,0 +1,149 @@
+use serde::Serialize;
+
+use super::fragment::Fragment;
+
+// A section of the document with its heading, text blocks and subsections
+#[derive(Serialize, Default)]
+pub struct Section {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub heading: Option<String>,
+    // Level of the heading (1-6), or 0 for sections without heading
+    pub level: u8,
+    pub blocks: Vec<String>,
+    pub sections: Vec<Section>,
+}
+
+// An open section while building the tree. Sections are started either by a sectioning element or by a heading.
+struct OpenSection {
+    section: Section,
+    sectioning_element: Option<usize>,
+}
+
+// Builds the tree of sections from the fragments of a document
+pub struct SectionTree {
+    // Open sections from the root to the innermost one
+    stack: Vec<OpenSection>,
+}
+
+impl SectionTree {
+    // Build the tree and return the top level sections. Content before the first heading is returned in a section without heading.
+    pub fn build(fragments: Vec<Fragment>) -> Vec<Section> {
+        let mut tree = SectionTree {
+            stack: vec![OpenSection { section: Section::default(), sectioning_element: None }],
+        };
+
+        for fragment in fragments.into_iter().filter(|fragment| !fragment.text.is_empty()) {
+            tree.enter_sectioning_elements(&fragment.sections);
+
+            match fragment.heading_level {
+                Some(level) => tree.add_heading(fragment.text, level),
+                None => tree.current().blocks.push(fragment.text),
+            }
+        }
+
+        while tree.stack.len() > 1 {
+            tree.close();
+        }
+        let mut root = tree.stack.pop().expect("Root section is never closed.").section;
+
+        let mut sections = Vec::new();
+        if !root.blocks.is_empty() {
+            sections.push(Section { blocks: std::mem::take(&mut root.blocks), ..Default::default() });
+        }
+        sections.append(&mut root.sections);
+        sections
+    }
+
+    fn current(&mut self) -> &mut Section {
+        &mut self.stack.last_mut().expect("Root section is never closed.").section
+    }
+
+    // Close the sections of the sectioning elements not containing the fragment and open the new ones
+    fn enter_sectioning_elements(&mut self, sectioning_elements: &[usize]) {
+        while let Some(position) = self.stack.iter().position(|open| {
+            open.sectioning_element.is_some_and(|element| !sectioning_elements.contains(&element))
+        }) {
+            while self.stack.len() > position {
+                self.close();
+            }
+        }
+
+        for element in sectioning_elements {
+            if !self.stack.iter().any(|open| open.sectioning_element == Some(*element)) {
+                self.stack.push(OpenSection { section: Section::default(), sectioning_element: Some(*element) });
+            }
+        }
+    }
+
+    fn add_heading(&mut self, heading: String, level: u8) {
+        // The first heading of a sectioning element is the heading of its section
+        let open = self.stack.last_mut().expect("Root section is never closed.");
+        if open.sectioning_element.is_some() && open.section.heading.is_none() && open.section.blocks.is_empty() && open.section.sections.is_empty() {
+            open.section.heading = Some(heading);
+            open.section.level = level;
+            return;
+        }
+
+        // Headings close the sections of the same or lower rank headings inside the current sectioning element
+        while self.stack.len() > 1 {
+            let open = self.stack.last().expect("Stack is not empty.");
+            if open.sectioning_element.is_some() || open.section.level < level {
+                break;
+            }
+            self.close();
+        }
+
+        self.stack.push(OpenSection {
+            section: Section { heading: Some(heading), level, ..Default::default() },
+            sectioning_element: None,
+        });
+    }
+
+    fn close(&mut self) {
+        let open = self.stack.pop().expect("Stack is not empty.");
+        if !open.section.blocks.is_empty() || !open.section.sections.is_empty() || open.section.heading.is_some() {
+            self.current().sections.push(open.section);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_section_tree() {
+        let fragment = |text: &str, heading_level: Option<u8>, sections: &[usize]| {
+            Fragment { text: String::from(text), heading_level, sections: sections.to_vec(), ..Default::default() }
+        };
+        let fragments = vec![
+            fragment("", None, &[]),
+            fragment("Intro", None, &[]),
+            fragment("Title", Some(1), &[]),
+            fragment("Text 1", None, &[]),
+            fragment("Chapter 1", Some(2), &[]),
+            fragment("Text 2", None, &[]),
+            fragment("Part", Some(3), &[]),
+            fragment("Text 3", None, &[]),
+            fragment("Chapter 2", Some(2), &[]),
+            fragment("Section", Some(3), &[1]),
+            fragment("Text 4", None, &[1]),
+            fragment("Text 5", None, &[]),
+        ];
+
+        // The first heading of a sectioning element is its heading, and the text after the element continues the
+        // section containing it
+        let sections = serde_json::to_value(SectionTree::build(fragments)).unwrap();
+        assert_eq!(sections, serde_json::json!([
+            { "level": 0, "blocks": ["Intro"], "sections": [] },
+            { "heading": "Title", "level": 1, "blocks": ["Text 1"], "sections": [
+                { "heading": "Chapter 1", "level": 2, "blocks": ["Text 2"], "sections": [
+                    { "heading": "Part", "level": 3, "blocks": ["Text 3"], "sections": [] }
+                ] },
+                { "heading": "Chapter 2", "level": 2, "blocks": ["Text 5"], "sections": [
+                    { "heading": "Section", "level": 3, "blocks": ["Text 4"], "sections": [] }
+                ] }
+            ] }
+        ]));
+    }
+}
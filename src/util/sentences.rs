@@ -0,0 +1,120 @@
+use unicode_segmentation::UnicodeSegmentation;
+
+// Abbreviations of common languages, which are followed by a full stop but don't end the sentence
+const ABBREVIATIONS: &[&str] = &[
+    // English
+    "mr", "mrs", "ms", "dr", "prof", "sr", "jr", "st", "vs", "e.g", "i.e", "inc", "ltd", "co", "corp", "vol", "jan", "feb",
+    "apr", "aug", "sep", "sept", "oct", "nov", "approx", "dept", "fig", "gov", "lt", "sgt", "capt", "rev", "mt", "ft", "u.s", "u.k",
+    // German
+    "bzw", "ca", "d.h", "evtl", "ggf", "hr", "fr", "nr", "str", "u.a", "vgl", "z.b", "z.t", "inkl",
+    // French
+    "mme", "mlle", "cf", "env", "p.ex", "av", "bd",
+    // Spanish, Italian and Portuguese
+    "sra", "srta", "dra", "ud", "uds", "pág", "sig", "sig.ra", "dott", "ing", "avv", "pag", "exmo", "exma",
+    // Dutch
+    "dhr", "mevr", "bijv", "blz", "o.a",
+    // Hungarian
+    "pl", "kb", "ún", "vö", "özv",
+];
+
+// Months written after ordinal day numbers (like '3. Oktober')
+const MONTHS: &[&str] = &[
+    "januar", "jänner", "februar", "märz", "april", "mai", "juni", "juli", "august", "september", "oktober", "november", "dezember",
+];
+
+// Split text into sentences using the Unicode sentence boundaries (UAX #29), without splitting after abbreviations,
+// initials and ordinal numbers
+pub struct SentenceSplitter {}
+
+impl SentenceSplitter {
+    // Return the start and end character offsets of the sentences (without the whitespace around them)
+    pub fn split(text: &str) -> Vec<(usize, usize)> {
+        let mut sentences: Vec<(usize, usize)> = Vec::new();
+        let mut continues_sentence = false;
+        let mut ends_with_day = false;
+        // Character offset of the current segment (segments follow each other, so offsets are counted incrementally)
+        let mut offset = 0;
+        for segment in text.split_sentence_bounds() {
+            let segment_start = offset;
+            offset += segment.chars().count();
+            let trimmed = segment.trim();
+            if trimmed.is_empty() {
+                continue;
+            }
+            let start = segment_start + segment.chars().take_while(|ch| ch.is_whitespace()).count();
+            let end = start + trimmed.chars().count();
+
+            let first_word = trimmed.split(|ch: char| !ch.is_alphabetic()).next().unwrap_or("").to_lowercase();
+            match sentences.last_mut() {
+                Some(last) if continues_sentence || (ends_with_day && MONTHS.contains(&first_word.as_str())) => last.1 = end,
+                _ => sentences.push((start, end)),
+            }
+            continues_sentence = SentenceSplitter::ends_with_abbreviation(trimmed);
+            ends_with_day = SentenceSplitter::ends_with_day(trimmed);
+        }
+        sentences
+    }
+
+    // Check whether the text ends with a number which can be the ordinal number of a day
+    fn ends_with_day(sentence: &str) -> bool {
+        let Some(without_stop) = sentence.strip_suffix('.') else {
+            return false;
+        };
+        let word = without_stop.rsplit(|ch: char| !ch.is_ascii_digit()).next().unwrap_or("");
+        (1..=2).contains(&word.len()) && without_stop[..without_stop.len() - word.len()].ends_with(|ch: char| ch.is_whitespace())
+    }
+
+    fn ends_with_abbreviation(sentence: &str) -> bool {
+        let Some(without_stop) = sentence.strip_suffix('.') else {
+            return false;
+        };
+        let word = without_stop.rsplit(|ch: char| ch.is_whitespace() || ch == '(' || ch == '"').next().unwrap_or("");
+        SentenceSplitter::is_abbreviation(word, word == without_stop)
+    }
+
+    // Check whether a word followed by a full stop doesn't end the sentence
+    pub fn is_abbreviation(word: &str, starts_sentence: bool) -> bool {
+        // Initials of names (like 'J. Smith') and numbers of list items (like '1. First item')
+        let is_initial = word.chars().count() == 1 && word.chars().all(char::is_uppercase) && word != "I";
+        let is_ordinal = starts_sentence && word.chars().all(|ch| ch.is_ascii_digit());
+        is_initial || is_ordinal || ABBREVIATIONS.contains(&word.to_lowercase().as_str())
+    }
+
+    pub fn is_month(word: &str) -> bool {
+        MONTHS.contains(&word.to_lowercase().as_str())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn sentences(text: &str) -> Vec<String> {
+        let chars: Vec<char> = text.chars().collect();
+        SentenceSplitter::split(text).into_iter().map(|(start, end)| chars[start..end].iter().collect()).collect()
+    }
+
+    #[test]
+    fn test_split() {
+        let text = "Dr. Smith met J. Doe in the U.S. on Monday. They talked about e.g. prices! Did they agree?";
+        assert_eq!(SentenceSplitter::split(text), vec![(0, 43), (44, 74), (75, 90)]);
+        assert_eq!(sentences("Der Termin ist z.B. am 3. Oktober. Danach beginnt die Arbeit."), vec!["Der Termin ist z.B. am 3. Oktober.", "Danach beginnt die Arbeit."]);
+        assert_eq!(sentences("1. Item"), vec!["1. Item"]);
+        assert_eq!(sentences("  Größe: 3. Danach kommt I. Ende.  "), vec!["Größe: 3.", "Danach kommt I.", "Ende."]);
+        assert!(sentences(" ").is_empty());
+    }
+
+    #[test]
+    fn test_abbreviations() {
+        assert!(SentenceSplitter::is_abbreviation("Dr", false));
+        assert!(SentenceSplitter::is_abbreviation("e.g", false));
+        assert!(SentenceSplitter::is_abbreviation("J", false));
+        assert!(!SentenceSplitter::is_abbreviation("I", false));
+        // Numbers are ordinals only at the start of a sentence (like the number of a list item)
+        assert!(SentenceSplitter::is_abbreviation("12", true));
+        assert!(!SentenceSplitter::is_abbreviation("12", false));
+        assert!(!SentenceSplitter::is_abbreviation("Monday", false));
+        assert!(SentenceSplitter::is_month("Oktober"));
+        assert!(!SentenceSplitter::is_month("Montag"));
+    }
+}
@@ -0,0 +1,286 @@
+use serde::Serialize;
+
+use super::{analyzer::Analyzer, fragment::Fragment, json_writer::JsonWriter, sentences::SentenceSplitter};
+use crate::text_extractor::ExtractParameters;
+
+// Letters read as vowels when syllables are counted (groups of adjacent vowels are one syllable)
+const VOWELS: &str = "aeiouyàáâãäåæèéêëìíîïòóôõöøùúûüýÿőűąęœаеёиоуыэюяіїє";
+
+// Variants of the Flesch reading ease formula adapted to languages other than English: the language, the name of the
+// formula, its constant and the weights of the average sentence length (in words) and word length (in syllables). Texts
+// of other languages (like Hungarian, Polish and Ukrainian) get no readability score of their language, because the
+// Flesch formulas were not adapted to them.
+const READABILITY_FORMULAS: [(&str, &str, f64, f64, f64); 7] = [
+    ("de", "Amstad", 180.0, 1.0, 58.5),
+    ("fr", "Kandel-Moles", 207.0, 1.015, 73.6),
+    ("es", "Szigriszt-Pazos", 206.835, 1.0, 62.3),
+    ("it", "Flesch-Vacca", 217.0, 1.3, 60.0),
+    ("nl", "Flesch-Douma", 206.835, 0.93, 77.0),
+    ("pt", "Flesch-Martins", 248.835, 1.015, 84.6),
+    ("ru", "Oborneva", 206.835, 1.3, 60.1),
+];
+
+// Closing punctuation allowed after the mark ending a sentence (like '."')
+const CLOSING: [char; 7] = ['"', '\'', ')', ']', '»', '”', '’'];
+
+// Counts of the text of a fragment. Counts of the fragments are summed for the whole document.
+#[derive(Default, Clone, Copy)]
+pub struct TextCounts {
+    characters: usize,
+    words: usize,
+    // Number of letters and digits in the words
+    word_characters: usize,
+    sentences: usize,
+    paragraphs: usize,
+    syllables: usize,
+    link_characters: usize,
+}
+
+// Statistics of a text written to the output
+#[derive(Serialize)]
+pub struct TextStats {
+    pub characters: usize,
+    pub words: usize,
+    pub sentences: usize,
+    pub paragraphs: usize,
+    pub average_word_length: f64,
+    pub link_ratio: f64,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub flesch_reading_ease: Option<f64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub flesch_kincaid_grade: Option<f64>,
+    // Reading ease by the variant of the Flesch formula adapted to the language of the text (see READABILITY_FORMULAS)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub readability: Option<Readability>,
+}
+
+#[derive(Serialize)]
+pub struct Readability {
+    pub language: &'static str,
+    pub formula: &'static str,
+    pub score: f64,
+}
+
+// Collect the counts of a text while its characters are written, so it is not read again when its fragment is completed.
+// Words are counted at their end, and whether a full stop ends the sentence is decided by the word after it.
+#[derive(Default)]
+pub struct TextCounter {
+    counts: TextCounts,
+    // Primary language of the text used to count syllables
+    language: String,
+    word: String,
+    // Number of words in the current sentence
+    sentence_words: usize,
+    // Whether the last word ended with a full stop, and whether it was the ordinal number of a day (like '3. Oktober')
+    stop: bool,
+    day: bool,
+}
+
+impl TextCounter {
+    pub fn new(language: String) -> Self {
+        Self { language, ..Default::default() }
+    }
+
+    pub fn push(&mut self, ch: char) {
+        self.counts.characters += 1;
+        if ch.is_whitespace() {
+            self.end_word();
+            // Lines of preformatted text are separate sentences
+            if ch == '\n' {
+                self.end_sentence();
+            }
+        } else {
+            self.word.push(ch);
+            // Sentences of languages written without spaces end in the middle of the words
+            if matches!(ch, '。' | '！' | '？') {
+                self.end_word();
+            }
+        }
+    }
+
+    pub fn finish(mut self, link_characters: usize) -> TextCounts {
+        self.end_word();
+        self.end_sentence();
+        if self.counts.words > 0 {
+            self.counts.sentences = self.counts.sentences.max(1);
+            self.counts.paragraphs = 1;
+        }
+        self.counts.link_characters = link_characters;
+        self.counts
+    }
+
+    fn end_word(&mut self) {
+        let word = std::mem::take(&mut self.word);
+        let letters: String = word.chars().filter(|ch| ch.is_alphanumeric()).collect();
+        if !letters.is_empty() {
+            // The sentence continues after a full stop if the next word starts with a lowercase letter (like 'etc. and')
+            if self.stop {
+                let continues = letters.starts_with(char::is_lowercase) || (self.day && SentenceSplitter::is_month(&letters));
+                if !continues {
+                    self.end_sentence();
+                }
+                self.stop = false;
+            }
+            self.counts.words += 1;
+            self.counts.word_characters += letters.chars().count();
+            self.counts.syllables += TextCounts::syllables(&letters, &self.language);
+            self.sentence_words += 1;
+        }
+        if self.sentence_words == 0 {
+            return;
+        }
+
+        let word = word.trim_end_matches(CLOSING);
+        if word.ends_with(['!', '?', '。', '！', '？']) {
+            self.end_sentence();
+        } else if let Some(without_stop) = word.strip_suffix('.') {
+            let without_stop = without_stop.trim_start_matches(['(', '"']);
+            if !SentenceSplitter::is_abbreviation(without_stop, self.sentence_words == 1) {
+                self.stop = true;
+                self.day = (1..=2).contains(&without_stop.len()) && without_stop.chars().all(|ch| ch.is_ascii_digit());
+            }
+        }
+    }
+
+    fn end_sentence(&mut self) {
+        if self.sentence_words > 0 {
+            self.counts.sentences += 1;
+            self.sentence_words = 0;
+        }
+        self.stop = false;
+    }
+}
+
+impl TextCounts {
+    // Count a text which was changed after its characters were written (syllables are counted by the rules of its language)
+    pub fn new(text: &str, link_characters: usize, language: &str) -> Self {
+        let mut counter = TextCounter::new(String::from(language));
+        text.chars().for_each(|ch| counter.push(ch));
+        counter.finish(link_characters)
+    }
+
+    // Primary subtag of a language tag (like 'en' of 'en-US'), English is the default
+    pub fn primary_language(language: Option<&str>) -> String {
+        language.and_then(|language| language.split(['-', '_']).next()).filter(|language| !language.is_empty()).unwrap_or("en").to_lowercase()
+    }
+
+    pub fn add(&mut self, other: &TextCounts) {
+        self.characters += other.characters;
+        self.words += other.words;
+        self.word_characters += other.word_characters;
+        self.sentences += other.sentences;
+        self.paragraphs += other.paragraphs;
+        self.syllables += other.syllables;
+        self.link_characters += other.link_characters;
+    }
+
+    pub fn stats(&self, language: &str) -> TextStats {
+        let round = |value: f64| (value * 100.0).round() / 100.0;
+        let ratio = |count: usize, total: usize| if total > 0 { round(count as f64 / total as f64) } else { 0.0 };
+
+        // Average sentence length in words and average word length in syllables
+        let (sentence_length, word_length) = (self.words as f64 / self.sentences.max(1) as f64, self.syllables as f64 / self.words.max(1) as f64);
+        let has_words = self.words > 0;
+        TextStats {
+            characters: self.characters,
+            words: self.words,
+            sentences: self.sentences,
+            paragraphs: self.paragraphs,
+            average_word_length: ratio(self.word_characters, self.words),
+            link_ratio: ratio(self.link_characters, self.characters),
+            flesch_reading_ease: has_words.then(|| round(206.835 - 1.015 * sentence_length - 84.6 * word_length)),
+            flesch_kincaid_grade: has_words.then(|| round(0.39 * sentence_length + 11.8 * word_length - 15.59)),
+            readability: has_words.then(|| TextCounts::readability(language, sentence_length, word_length)).flatten(),
+        }
+    }
+
+    // Reading ease by the formula adapted to the language (None for English and for the languages without a formula)
+    fn readability(language: &str, sentence_length: f64, word_length: f64) -> Option<Readability> {
+        let (language, formula, constant, sentence_weight, word_weight) = READABILITY_FORMULAS.iter().find(|(code, ..)| *code == language)?;
+        let score = constant - sentence_weight * sentence_length - word_weight * word_length;
+        Some(Readability { language, formula, score: (score * 100.0).round() / 100.0 })
+    }
+
+    // Count the groups of vowels in a word (a final silent 'e' is not counted in English)
+    fn syllables(word: &str, language: &str) -> usize {
+        let chars: Vec<char> = word.chars().flat_map(char::to_lowercase).collect();
+        let mut count = 0;
+        let mut previous_vowel = false;
+        for ch in chars.iter() {
+            let vowel = VOWELS.contains(*ch);
+            if vowel && !previous_vowel {
+                count += 1;
+            }
+            previous_vowel = vowel;
+        }
+        if language == "en" && count > 1 && chars.ends_with(&['e']) && !chars.ends_with(&['l', 'e']) {
+            count -= 1;
+        }
+        count.max(1)
+    }
+}
+
+// Counts of the document are the sums of the counts of its fragments. Readability depends on the language of the document
+// (English is used if it is not known).
+impl Analyzer for TextCounts {
+    fn add(&mut self, fragment: &Fragment) {
+        TextCounts::add(self, &fragment.counts);
+    }
+
+    fn write_result(&mut self, _parameters: &ExtractParameters, language: Option<&str>, output: &mut String) {
+        output.push_str(&JsonWriter::field("stats", &self.stats(language.unwrap_or("en"))));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn stats(text: &str, link_characters: usize, language: &str) -> serde_json::Value {
+        serde_json::to_value(TextCounts::new(text, link_characters, language).stats(language)).unwrap()
+    }
+
+    #[test]
+    fn test_counts() {
+        assert_eq!(stats("The cat sat on the mat. It was happy.", 5, "en"), serde_json::json!({
+            "characters": 37, "words": 9, "sentences": 2, "paragraphs": 1, "average_word_length": 3.0, "link_ratio": 0.14,
+            "flesch_reading_ease": 108.27, "flesch_kincaid_grade": -0.72
+        }));
+        assert_eq!(stats("Das ist gut.", 0, "de")["readability"], serde_json::json!({ "language": "de", "formula": "Amstad", "score": 118.5 }));
+        assert_eq!(stats("Это хорошо.", 0, "ru")["readability"]["formula"], "Oborneva");
+
+        // Abbreviations and ordinal days don't end the sentences, but new line characters do
+        assert_eq!(stats("Dr. Smith arrived on 3. Oktober. He was late!", 0, "en")["sentences"], 2);
+        assert_eq!(stats("First line\nSecond line", 0, "en")["sentences"], 2);
+        assert_eq!(stats("", 0, "en"), serde_json::json!({
+            "characters": 0, "words": 0, "sentences": 0, "paragraphs": 0, "average_word_length": 0.0, "link_ratio": 0.0
+        }));
+
+        // Counts of the fragments are summed for the document
+        let mut counts = TextCounts::new("The cat sat on the mat. It was happy.", 5, "en");
+        counts.add(&TextCounts::new("Das ist gut.", 0, "de"));
+        let stats = counts.stats("en");
+        assert_eq!((stats.words, stats.sentences, stats.paragraphs, stats.link_ratio), (12, 3, 2, 0.1));
+    }
+
+    #[test]
+    fn test_readability() {
+        // Only the Flesch scores are reported for English and for the languages without an adapted formula
+        for language in ["en", "hu", "pl", "uk", "ja"] {
+            let stats = TextCounts::new("Ez egy rövid mondat.", 0, language).stats(language);
+            assert!(stats.readability.is_none(), "{}", language);
+            assert!(stats.flesch_reading_ease.is_some(), "{}", language);
+        }
+        assert!(TextCounts::readability("pt", 10.0, 1.5).is_some_and(|readability| readability.score == 111.79));
+    }
+
+    #[test]
+    fn test_syllables() {
+        assert_eq!(TextCounts::syllables("cake", "en"), 1);
+        assert_eq!(TextCounts::syllables("table", "en"), 2);
+        assert_eq!(TextCounts::syllables("cake", "de"), 2);
+        assert_eq!(TextCounts::syllables("Rhythm", "en"), 1);
+        assert_eq!(TextCounts::primary_language(Some("en-US")), "en");
+        assert_eq!(TextCounts::primary_language(None), "en");
+    }
+}
@@ -0,0 +1,204 @@
+use serde::Serialize;
+
+use super::{analyzer::Analyzer, fragment::Fragment, json_writer::JsonWriter, keywords::KeywordExtractor, main_content::MainContent, sentences::SentenceSplitter};
+use crate::text_extractor::ExtractParameters;
+
+// Damping factor of the ranking and the limits of the iteration
+const DAMPING: f64 = 0.85;
+const MAX_ITERATIONS: usize = 100;
+const CONVERGENCE: f64 = 0.000001;
+// Only the first sentences of long documents are ranked (the similarity of each pair of sentences is computed)
+const MAX_SENTENCES: usize = 1000;
+// Sentences with less words (without stopwords) are not selected
+const MIN_WORDS: usize = 3;
+
+// A selected sentence with its character offsets in the text of the Simple format
+#[derive(Serialize)]
+pub struct SummarySentence {
+    pub text: String,
+    pub start: usize,
+    pub end: usize,
+    // Score relative to the best sentence
+    pub score: f64,
+}
+
+struct Candidate {
+    // Index of the fragment containing the sentence
+    fragment: usize,
+    text: String,
+    start: usize,
+    end: usize,
+    // Words of the sentence in lowercase
+    words: Vec<String>,
+}
+
+// Select the most representative sentences of the main content of a document with TextRank. Sentences are ranked like
+// web pages linked by the similarity of their words, so sentences similar to many other sentences get the highest scores.
+pub struct Summarizer {
+    candidates: Vec<Candidate>,
+    // Whether the main content is selected from the fragments (it is not needed if only the main content is written),
+    // and the main content scores of the fragments and whether they are link lists
+    select_main_content: bool,
+    fragments: Vec<(f64, bool)>,
+    // Length of the text added so far (fragments are separated by new line characters)
+    length: Option<usize>,
+}
+
+impl Summarizer {
+    pub fn new(select_main_content: bool) -> Self {
+        Self { candidates: Vec::new(), select_main_content, fragments: Vec::new(), length: None }
+    }
+
+    // Headings, the title and code blocks are not summarized, but their length is counted in the offsets
+    pub fn add(&mut self, fragment: &Fragment) {
+        let offset = self.length.map_or(0, |length| length + 1);
+        let chars: Vec<char> = fragment.text.chars().collect();
+        self.length = Some(offset + chars.len());
+        let index = self.fragments.len();
+        if self.select_main_content {
+            self.fragments.push((MainContent::score(fragment), MainContent::is_link_list(fragment)));
+        }
+        if fragment.heading_level.is_some() || fragment.title || fragment.code {
+            return;
+        }
+
+        for (start, end) in SentenceSplitter::split(&fragment.text) {
+            if self.candidates.len() >= MAX_SENTENCES {
+                return;
+            }
+            let text: String = chars[start..end].iter().collect();
+            let words = text.split(|ch: char| !ch.is_alphanumeric()).filter(|word| !word.is_empty()).map(str::to_lowercase).collect();
+            self.candidates.push(Candidate { fragment: index, text, start: offset + start, end: offset + end, words });
+        }
+    }
+
+    // Return the best sentences of the main content in the order of the document
+    pub fn summarize(&self, count: usize, language: Option<&str>) -> Vec<SummarySentence> {
+        let candidates: Vec<&Candidate> = if self.select_main_content {
+            let scores: Vec<f64> = self.fragments.iter().map(|(score, _)| *score).collect();
+            let (start, end) = MainContent::best_range(&scores);
+            self.candidates.iter().filter(|candidate| (start..end).contains(&candidate.fragment) && !self.fragments[candidate.fragment].1).collect()
+        } else {
+            self.candidates.iter().collect()
+        };
+
+        let stopwords = KeywordExtractor::stopwords(language);
+        let words: Vec<Vec<&str>> = candidates
+            .iter()
+            .map(|candidate| {
+                let mut words: Vec<&str> = candidate.words.iter().map(String::as_str).filter(|word| !stopwords.contains(word)).collect();
+                words.sort_unstable();
+                words.dedup();
+                words
+            })
+            .collect();
+
+        // Similarity of sentences is the number of their common words normalized by the logarithm of their lengths
+        let size = words.len();
+        let mut similarities = vec![vec![0.0; size]; size];
+        for first in 0..size {
+            for second in first + 1..size {
+                let common = words[first].iter().filter(|word| words[second].binary_search(word).is_ok()).count();
+                let norm = (words[first].len() as f64).ln() + (words[second].len() as f64).ln();
+                if common > 0 && norm > 0.0 {
+                    similarities[first][second] = common as f64 / norm;
+                    similarities[second][first] = common as f64 / norm;
+                }
+            }
+        }
+        let totals: Vec<f64> = similarities.iter().map(|row| row.iter().sum()).collect();
+
+        let mut scores = vec![1.0; size];
+        for _ in 0..MAX_ITERATIONS {
+            let next: Vec<f64> = (0..size)
+                .map(|sentence| {
+                    let rank: f64 = (0..size)
+                        .filter(|other| totals[*other] > 0.0)
+                        .map(|other| similarities[other][sentence] / totals[other] * scores[other])
+                        .sum();
+                    1.0 - DAMPING + DAMPING * rank
+                })
+                .collect();
+            let change: f64 = next.iter().zip(scores.iter()).map(|(a, b)| (a - b).abs()).sum();
+            scores = next;
+            if change < CONVERGENCE {
+                break;
+            }
+        }
+
+        let mut ranked: Vec<usize> = (0..size).filter(|sentence| words[*sentence].len() >= MIN_WORDS).collect();
+        ranked.sort_by(|a, b| scores[*b].total_cmp(&scores[*a]).then_with(|| a.cmp(b)));
+        ranked.truncate(count);
+        let best = ranked.first().map_or(1.0, |sentence| scores[*sentence]);
+        ranked.sort_unstable();
+        ranked
+            .into_iter()
+            .map(|sentence| {
+                let candidate = candidates[sentence];
+                SummarySentence {
+                    text: candidate.text.clone(),
+                    start: candidate.start,
+                    end: candidate.end,
+                    score: (scores[sentence] / best * 100.0).round() / 100.0,
+                }
+            })
+            .collect()
+    }
+}
+
+impl Analyzer for Summarizer {
+    fn add(&mut self, fragment: &Fragment) {
+        Summarizer::add(self, fragment);
+    }
+
+    fn write_result(&mut self, parameters: &ExtractParameters, language: Option<&str>, output: &mut String) {
+        output.push_str(&JsonWriter::array_field("summary", &self.summarize(parameters.summary, language)));
+    }
+
+    fn needs_language(&self) -> bool {
+        true
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn fragment(text: &str, heading_level: Option<u8>, hint: i32) -> Fragment {
+        Fragment { text: String::from(text), heading_level, hint, ..Default::default() }
+    }
+
+    fn summary(summarizer: &Summarizer, count: usize) -> Vec<(String, usize, usize)> {
+        summarizer.summarize(count, Some("en")).into_iter().map(|sentence| (sentence.text, sentence.start, sentence.end)).collect()
+    }
+
+    #[test]
+    fn test_summary() {
+        let mut summarizer = Summarizer::new(false);
+        summarizer.add(&fragment("Solar energy", Some(1), 0));
+        summarizer.add(&fragment("Solar panels convert sunlight into electricity for homes. The weather was nice yesterday. \
+            Modern solar panels produce more electricity than older panels.", None, 0));
+        summarizer.add(&fragment("Batteries store the electricity of solar panels for the night. My cat likes to sleep all day long.", None, 0));
+
+        // Sentences are ranked by their similarity to the others and returned in the order of the document
+        assert_eq!(summary(&summarizer, 2), vec![
+            (String::from("Solar panels convert sunlight into electricity for homes."), 13, 70),
+            (String::from("Modern solar panels produce more electricity than older panels."), 103, 166)
+        ]);
+        assert_eq!(summary(&summarizer, 10).len(), 5);
+        assert!(summary(&Summarizer::new(false), 2).is_empty());
+    }
+
+    #[test]
+    fn test_summary_of_main_content() {
+        // Sentences of the navigation and the footer are not selected if the main content is selected by the summarizer
+        let mut summarizer = Summarizer::new(true);
+        summarizer.add(&fragment("Solar panels shop. Buy solar panels and solar electricity online today.", None, -50));
+        summarizer.add(&fragment("Solar panels convert sunlight into electricity for homes. The weather was nice yesterday. Modern solar panels \
+            produce more electricity than older panels.", None, 25));
+        summarizer.add(&fragment("Batteries store the electricity of solar panels for the night.", None, 25));
+        summarizer.add(&fragment("Subscribe to the solar panels newsletter for solar electricity news.", None, -50));
+        let texts: Vec<String> = summary(&summarizer, 2).into_iter().map(|(text, _, _)| text).collect();
+        assert_eq!(texts, vec!["Solar panels convert sunlight into electricity for homes.", "Modern solar panels produce more electricity than older panels."]);
+    }
+}
@@ -0,0 +1,76 @@
+use serde::Serialize;
+
+// A table of the document with cells of merged rows and columns repeated in each row and column
+#[derive(Serialize, Default)]
+pub struct Table {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub caption: Option<String>,
+    pub header: Vec<Vec<String>>,
+    pub rows: Vec<Vec<String>>,
+}
+
+impl Table {
+    // Render the table as tab separated values (one line for each row)
+    pub fn to_tsv(&self) -> String {
+        let mut lines = Vec::new();
+        for row in self.header.iter().chain(self.rows.iter()) {
+            let cells: Vec<String> = row.iter().map(|cell| cell.replace(['\t', '\n'], " ")).collect();
+            lines.push(cells.join("\t"));
+        }
+        lines.join("\n")
+    }
+
+    // Render the table in Markdown format. The first row is used as header if the table has no header rows.
+    pub fn to_markdown(&self) -> String {
+        let mut lines = Vec::new();
+        let columns = self.header.iter().chain(self.rows.iter()).map(|row| row.len()).max().unwrap_or(0);
+        let render_row = |row: &Vec<String>| {
+            let mut cells: Vec<String> = row.iter().map(|cell| cell.replace('|', "\\|").replace('\n', " ")).collect();
+            cells.resize(columns, String::new());
+            format!("| {} |", cells.join(" | "))
+        };
+
+        let mut rows = self.header.iter().chain(self.rows.iter());
+        let header_count = self.header.len().max(1);
+        for (index, row) in rows.by_ref().take(header_count).enumerate() {
+            lines.push(render_row(row));
+            if index == header_count - 1 {
+                lines.push(format!("|{}", " --- |".repeat(columns)));
+            }
+        }
+        lines.extend(rows.map(render_row));
+        lines.join("\n")
+    }
+
+    // Text of all cells in one line
+    pub fn to_text(&self) -> String {
+        let cells: Vec<&str> = self.header.iter().chain(self.rows.iter()).flatten().map(|cell| cell.as_str()).collect();
+        cells.join(" ")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn row(cells: &[&str]) -> Vec<String> {
+        cells.iter().map(|cell| String::from(*cell)).collect()
+    }
+
+    #[test]
+    fn test_table_rendering() {
+        let table = Table {
+            caption: Some(String::from("Fruits")),
+            header: vec![row(&["Name", "Size", "Price"])],
+            rows: vec![row(&["Apple", "Small", "1"]), row(&["Pear | Any", "Pear\tAny", "3"]), row(&["Plum"])],
+        };
+        assert_eq!(table.to_tsv(), "Name\tSize\tPrice\nApple\tSmall\t1\nPear | Any\tPear Any\t3\nPlum");
+        assert_eq!(table.to_markdown(), "| Name | Size | Price |\n| --- | --- | --- |\n| Apple | Small | 1 |\n| Pear \\| Any | Pear\tAny | 3 |\n| Plum |  |  |");
+        assert_eq!(table.to_text(), "Name Size Price Apple Small 1 Pear | Any Pear\tAny 3 Plum");
+
+        // The first row is the header in Markdown if the table has no header rows
+        let table = Table { rows: vec![row(&["A", "B"]), row(&["1", "2"])], ..Default::default() };
+        assert_eq!(table.to_markdown(), "| A | B |\n| --- | --- |\n| 1 | 2 |");
+        assert_eq!(Table::default().to_markdown(), "");
+    }
+}
@@ -0,0 +1,42 @@
+use super::xml_reader::XmlElement;
+
+// Rules including and excluding XML elements by their names. A rule is a local name matching elements in any namespace
+// (like 'title'), a prefixed name matching the name written in the document (like 'dc:title'), a name with the
+// namespace URI in braces (like '{http://purl.org/dc/elements/1.1/}title') or '*' matching all elements.
+#[derive(Default)]
+pub struct ElementRules {
+    include: Vec<String>,
+    exclude: Vec<String>,
+}
+
+impl ElementRules {
+    pub fn new(include: Option<&str>, exclude: Option<&str>) -> Self {
+        let parse = |rules: Option<&str>| -> Vec<String> {
+            rules.into_iter().flat_map(|rules| rules.split(',')).map(str::trim).filter(|rule| !rule.is_empty()).map(String::from).collect()
+        };
+        Self { include: parse(include), exclude: parse(exclude) }
+    }
+
+    // Content of the document is included from the root unless only some elements are included
+    pub fn includes_root(&self) -> bool {
+        self.include.is_empty()
+    }
+
+    pub fn is_included(&self, element: &XmlElement) -> bool {
+        self.include.iter().any(|rule| ElementRules::matches(rule, element))
+    }
+
+    pub fn is_excluded(&self, element: &XmlElement) -> bool {
+        self.exclude.iter().any(|rule| ElementRules::matches(rule, element))
+    }
+
+    fn matches(rule: &str, element: &XmlElement) -> bool {
+        if rule == "*" || rule == element.name {
+            return true;
+        }
+        match rule.strip_prefix('{').and_then(|rule| rule.split_once('}')) {
+            Some((uri, rule_name)) => element.namespace.as_deref().unwrap_or("") == uri && rule_name == element.local_name(),
+            None => !rule.contains(':') && rule == element.local_name(),
+        }
+    }
+}
@@ -0,0 +1,437 @@
+use serde::Serialize;
+use tokio::io::{AsyncRead, AsyncWrite};
+
+use super::xml_reader::{XmlElement, XmlEvent, XmlReader};
+use crate::{
+    error::Error, html::html_text_extractor::HtmlTextExtractor, text_extractor::{ContentMode, ExtractParameters, InputFormat, OutputFormat},
+    util::{entities::EntityExtractor, json_writer::JsonWriter, redactor::Redactor, utf8_writer::Utf8Writer}
+};
+
+const ATOM_NAMESPACE: &str = "http://www.w3.org/2005/Atom";
+const RDF_NAMESPACE: &str = "http://www.w3.org/1999/02/22-rdf-syntax-ns#";
+const RSS1_NAMESPACE: &str = "http://purl.org/rss/1.0/";
+const CONTENT_NAMESPACE: &str = "http://purl.org/rss/1.0/modules/content/";
+const DC_NAMESPACE: &str = "http://purl.org/dc/elements/1.1/";
+
+const MONTHS: [&str; 12] = ["jan", "feb", "mar", "apr", "may", "jun", "jul", "aug", "sep", "oct", "nov", "dec"];
+
+// RSS 2.0 (and the earlier 0.9x versions), RSS 1.0 (RDF Site Summary) and Atom
+#[derive(Serialize, Clone, Copy, PartialEq)]
+#[serde(rename_all = "lowercase")]
+enum FeedFormat { Rss, Rdf, Atom }
+
+#[derive(Serialize)]
+struct Feed {
+    format: FeedFormat,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    title: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    link: Option<String>,
+    // Error of extracting the text of the title (the feed is parsed further)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    error: Option<String>,
+}
+
+// An item of an RSS feed or an entry of an Atom feed. The published date is in RFC 3339 format if it can be read.
+#[derive(Serialize, Default)]
+pub struct FeedItem {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub title: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub link: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub published: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub author: Option<String>,
+    pub text: String,
+    // Error of extracting the text of the item (the other items are written as well)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+}
+
+#[derive(Clone, Copy, PartialEq)]
+enum Field { Title, Link, Published, Updated, Author, Description, Content }
+
+// Type of the texts. Descriptions of RSS items are escaped HTML, texts of Atom feeds declare their type.
+#[derive(Clone, Copy, PartialEq)]
+enum TextType { Text, Html, Xhtml }
+
+struct OpenField {
+    field: Field,
+    depth: usize,
+    text_type: TextType,
+    // Text of the field (markup of XHTML content is written back as it is)
+    text: String,
+}
+
+struct OpenItem {
+    depth: usize,
+    item: FeedItem,
+    updated: Option<String>,
+    description: Option<(String, TextType)>,
+    content: Option<(String, TextType)>,
+}
+
+// Parser of RSS and Atom feeds writing a result for each item of the feed. The content of the items is extracted
+// with the HTML text extractor.
+pub struct FeedParser<'a, R: AsyncRead + Unpin + Send, W: AsyncWrite + Unpin + Send> {
+    input: XmlReader<'a, R>,
+    writer: Utf8Writer<'a, W>,
+    // Parameters of extracting the text of HTML content
+    html_parameters: ExtractParameters,
+    input_format: Option<InputFormat>,
+    // Personal data is replaced in all fields of the feed and the items (if redaction is requested)
+    redactor: Option<Redactor>,
+    format: Option<FeedFormat>,
+    // Local names of the open elements
+    elements: Vec<String>,
+    title: Option<String>,
+    link: Option<String>,
+    error: Option<String>,
+    item: Option<OpenItem>,
+    field: Option<OpenField>,
+    item_count: usize,
+}
+
+impl<'a, R: AsyncRead + Unpin + Send, W: AsyncWrite + Unpin + Send> FeedParser<'a, R, W> {
+    pub fn new(reader: &'a mut R, writer: &'a mut W, parameters: &ExtractParameters) -> Self {
+        // Document level results are not reported for the content of the items
+        let html_parameters = ExtractParameters {
+            output_format: OutputFormat::Simple,
+            input_format: None,
+            content_mode: ContentMode::Full,
+            include: None,
+            exclude: None,
+            profile: None,
+            tables: false,
+            paths: false,
+            detect_language: false,
+            stats: false,
+            keywords: 0,
+            summary: 0,
+            fingerprints: false,
+            entities: false,
+            redact: false,
+            ..parameters.clone()
+        };
+        Self {
+            input: XmlReader::new(reader),
+            writer: Utf8Writer::new(writer, 1024),
+            html_parameters,
+            input_format: parameters.input_format,
+            redactor: parameters.redact.then(Redactor::default),
+            format: None,
+            elements: Vec::new(),
+            title: None,
+            link: None,
+            error: None,
+            item: None,
+            field: None,
+            item_count: 0,
+        }
+    }
+
+    pub async fn parse(&mut self) -> Result<(), Error> {
+        self.writer.write_string("{\n \"results\": [\n").await?;
+        let result = self.parse_content().await;
+
+        let mut output = String::from("\n ]");
+        if let Some(input_format) = self.input_format {
+            output.push_str(&format!(",\n \"input_format\": {}", JsonWriter::to_json(&input_format)));
+        }
+        if let Some(format) = self.format {
+            let (title, link) = (self.title.take(), self.link.take());
+            let feed = Feed { format, title: self.redact(title), link: self.redact(link), error: self.error.take() };
+            output.push_str(&format!(",\n \"feed\": {}", JsonWriter::to_json(&feed)));
+        }
+        if let Some(redactor) = self.redactor.as_ref() {
+            output.push_str(&format!(",\n \"redactions\": {}", JsonWriter::to_json(&redactor.counts)));
+        }
+        if let Err(error) = result {
+            output.push_str(&format!(",\n \"error\": {}", JsonWriter::to_json(&error.message)));
+        }
+        output.push_str("\n}");
+        self.writer.write_string(&output).await
+    }
+
+    async fn parse_content(&mut self) -> Result<(), Error> {
+        while let Some(event) = self.input.next().await? {
+            match event {
+                XmlEvent::Start(element) => self.start_element(&element)?,
+                XmlEvent::End(name) => self.end_element(&name).await?,
+                XmlEvent::Text(text) => {
+                    if let Some(field) = self.field.as_mut() {
+                        match field.text_type {
+                            TextType::Xhtml => field.text.push_str(&escape(&text)),
+                            _ => field.text.push_str(&text),
+                        }
+                    }
+                }
+            }
+        }
+        Ok(())
+    }
+
+    fn start_element(&mut self, element: &XmlElement) -> Result<(), Error> {
+        let depth = self.elements.len();
+        let local_name = element.local_name();
+        self.elements.push(String::from(local_name));
+        let namespace = element.namespace.as_deref();
+
+        let Some(format) = self.format else {
+            self.format = Some(match (local_name, namespace) {
+                ("rss", None) => FeedFormat::Rss,
+                ("RDF", Some(RDF_NAMESPACE)) => FeedFormat::Rdf,
+                ("feed", Some(ATOM_NAMESPACE)) => FeedFormat::Atom,
+                _ => return Err(Error { eof: false, message: String::from("The document is not an RSS or Atom feed") }),
+            });
+            return Ok(());
+        };
+
+        // Elements of XHTML content are part of the text
+        if let Some(field) = self.field.as_mut() {
+            if field.text_type == TextType::Xhtml {
+                field.text.push_str(&format!("<{}", local_name));
+                for (attribute, value) in element.attributes.iter().filter(|(attribute, _)| !attribute.starts_with("xmlns")) {
+                    field.text.push_str(&format!(" {}=\"{}\"", attribute, escape(value)));
+                }
+                field.text.push('>');
+            }
+            return Ok(());
+        }
+
+        let parent = self.elements[depth - 1].as_str();
+        let item_depth = match &self.item {
+            Some(item) => item.depth,
+            None => {
+                let is_item = match format {
+                    FeedFormat::Rss => local_name == "item" && namespace.is_none(),
+                    FeedFormat::Rdf => local_name == "item" && namespace == Some(RSS1_NAMESPACE),
+                    FeedFormat::Atom => local_name == "entry" && namespace == Some(ATOM_NAMESPACE),
+                };
+                if is_item {
+                    self.item = Some(OpenItem { depth, item: FeedItem::default(), updated: None, description: None, content: None });
+                    return Ok(());
+                }
+                // Title and link of the feed are the ones of the channel
+                let is_channel = match format {
+                    FeedFormat::Rss | FeedFormat::Rdf => parent == "channel",
+                    FeedFormat::Atom => depth == 1,
+                };
+                if !is_channel {
+                    return Ok(());
+                }
+                depth - 1
+            }
+        };
+
+        let text_type = match element.attribute("type") {
+            Some("html") | Some("text/html") => TextType::Html,
+            Some("xhtml") | Some("application/xhtml+xml") => TextType::Xhtml,
+            _ => TextType::Text,
+        };
+        let field = match (format, namespace, local_name) {
+            _ if depth == item_depth + 2 && parent == "author" && local_name == "name" && format == FeedFormat::Atom => Some((Field::Author, text_type)),
+            _ if depth != item_depth + 1 => None,
+            (FeedFormat::Atom, Some(ATOM_NAMESPACE), "link") => {
+                if element.attribute("rel").unwrap_or("alternate") == "alternate" {
+                    if let Some(href) = element.attribute("href") {
+                        let link = match self.item.as_mut() {
+                            Some(item) => &mut item.item.link,
+                            None => &mut self.link,
+                        };
+                        link.get_or_insert_with(|| String::from(href.trim()));
+                    }
+                }
+                None
+            }
+            (FeedFormat::Atom, Some(ATOM_NAMESPACE), name) => match name {
+                "title" => Some((Field::Title, text_type)),
+                "published" => Some((Field::Published, text_type)),
+                "updated" => Some((Field::Updated, text_type)),
+                "summary" => Some((Field::Description, text_type)),
+                // Content outside of the feed (referred by the src attribute) is not loaded
+                "content" if element.attribute("src").is_none() => Some((Field::Content, text_type)),
+                _ => None,
+            },
+            (FeedFormat::Rss, None, name) | (FeedFormat::Rdf, Some(RSS1_NAMESPACE), name) => match name {
+                "title" => Some((Field::Title, TextType::Text)),
+                "link" => Some((Field::Link, TextType::Text)),
+                "pubDate" => Some((Field::Published, TextType::Text)),
+                "author" => Some((Field::Author, TextType::Text)),
+                "description" => Some((Field::Description, TextType::Html)),
+                _ => None,
+            },
+            (_, Some(CONTENT_NAMESPACE), "encoded") => Some((Field::Content, TextType::Html)),
+            (_, Some(DC_NAMESPACE), "creator") => Some((Field::Author, TextType::Text)),
+            (_, Some(DC_NAMESPACE), "date") => Some((Field::Published, TextType::Text)),
+            _ => None,
+        };
+        if let Some((field, text_type)) = field {
+            self.field = Some(OpenField { field, depth, text_type, text: String::new() });
+        }
+        Ok(())
+    }
+
+    async fn end_element(&mut self, name: &str) -> Result<(), Error> {
+        self.elements.pop();
+        let depth = self.elements.len();
+
+        if let Some(field) = self.field.as_mut() {
+            if field.depth != depth {
+                if field.text_type == TextType::Xhtml {
+                    field.text.push_str(&format!("</{}>", name.rsplit(':').next().unwrap_or(name)));
+                }
+                return Ok(());
+            }
+            if let Some(field) = self.field.take() {
+                self.end_field(field).await?;
+            }
+            return Ok(());
+        }
+
+        if self.item.as_ref().is_some_and(|item| item.depth == depth) {
+            if let Some(item) = self.item.take() {
+                self.write_item(item).await?;
+            }
+        }
+        Ok(())
+    }
+
+    async fn end_field(&mut self, field: OpenField) -> Result<(), Error> {
+        let text = field.text.trim();
+        let Some(item) = self.item.as_mut() else {
+            // Fields of the channel
+            match field.field {
+                Field::Title if self.title.is_none() && self.error.is_none() => {
+                    match FeedParser::<R, W>::to_text(text, field.text_type, &self.html_parameters).await {
+                        Ok(title) => self.title = Some(title),
+                        Err(error) => self.error = Some(error.message),
+                    }
+                }
+                Field::Link => {
+                    self.link.get_or_insert_with(|| String::from(text));
+                }
+                _ => {}
+            }
+            return Ok(());
+        };
+        match field.field {
+            Field::Title => {
+                if item.item.title.is_none() {
+                    let title = FeedParser::<R, W>::to_text(text, field.text_type, &self.html_parameters).await;
+                    if let Some(item) = self.item.as_mut() {
+                        match title {
+                            Ok(title) => item.item.title = Some(title),
+                            Err(error) => item.item.error = Some(error.message),
+                        }
+                    }
+                }
+            }
+            Field::Link => {
+                item.item.link.get_or_insert_with(|| String::from(text));
+            }
+            Field::Published => {
+                item.item.published.get_or_insert_with(|| rfc3339(text).unwrap_or_else(|| String::from(text)));
+            }
+            Field::Updated => {
+                item.updated.get_or_insert_with(|| String::from(text));
+            }
+            Field::Author => {
+                item.item.author.get_or_insert_with(|| String::from(text));
+            }
+            Field::Description => {
+                item.description.get_or_insert_with(|| (String::from(text), field.text_type));
+            }
+            Field::Content => {
+                item.content.get_or_insert_with(|| (String::from(text), field.text_type));
+            }
+        }
+        Ok(())
+    }
+
+    // Write an item with the text of its content (or its description or summary if it has no content)
+    async fn write_item(&mut self, item: OpenItem) -> Result<(), Error> {
+        let mut feed_item = item.item;
+        if feed_item.published.is_none() {
+            feed_item.published = item.updated;
+        }
+        if let Some((text, text_type)) = item.content.or(item.description) {
+            match FeedParser::<R, W>::to_text(&text, text_type, &self.html_parameters).await {
+                Ok(text) => feed_item.text = text,
+                Err(error) => feed_item.error = Some(error.message),
+            }
+        }
+        if let Some(redactor) = self.redactor.as_mut() {
+            feed_item.text = redactor.redact_text(&feed_item.text);
+        }
+        feed_item.title = self.redact(feed_item.title);
+        feed_item.link = self.redact(feed_item.link);
+        feed_item.author = self.redact(feed_item.author);
+
+        let separator = if self.item_count > 0 { ",\n" } else { "" };
+        self.item_count += 1;
+        self.writer.write_string(&format!("{}  {}", separator, JsonWriter::to_json(&feed_item))).await
+    }
+
+    fn redact(&mut self, text: Option<String>) -> Option<String> {
+        match self.redactor.as_mut() {
+            Some(redactor) => text.map(|text| redactor.redact_text(&text)),
+            None => text,
+        }
+    }
+
+    // Extract the text of HTML with the HTML text extractor
+    async fn to_text(text: &str, text_type: TextType, parameters: &ExtractParameters) -> Result<String, Error> {
+        if text_type == TextType::Text {
+            return Ok(text.split_whitespace().collect::<Vec<&str>>().join(" "));
+        }
+        let html = format!("<html><body>{}</body></html>", text);
+        let text = HtmlTextExtractor::new().text(&mut html.as_bytes(), parameters).await?;
+        Ok(text.trim().to_string())
+    }
+}
+
+fn escape(text: &str) -> String {
+    text.replace('&', "&amp;").replace('<', "&lt;").replace('>', "&gt;").replace('"', "&quot;")
+}
+
+// Convert a date of RSS feeds (in RFC 822 format, like 'Mon, 02 Jan 2006 15:04:05 GMT') to RFC 3339 format
+fn rfc3339(date: &str) -> Option<String> {
+    let date = date.split_once(',').map_or(date, |(_, date)| date);
+    let parts: Vec<&str> = date.split_whitespace().collect();
+    if parts.len() < 4 {
+        return None;
+    }
+    let day: u32 = parts[0].parse().ok()?;
+    let month_name = parts[1].to_lowercase();
+    let month = MONTHS.iter().position(|month| month_name.starts_with(month))? + 1;
+    let mut year: u32 = parts[2].parse().ok()?;
+    if parts[2].len() == 2 {
+        year += if year < 50 { 2000 } else { 1900 };
+    }
+    let time: Vec<u32> = parts[3].split(':').map(|part| part.parse().ok()).collect::<Option<Vec<u32>>>()?;
+    let (hour, minute, second) = match time[..] {
+        [hour, minute] => (hour, minute, 0),
+        [hour, minute, second] => (hour, minute, second),
+        _ => return None,
+    };
+    if hour > 23 || minute > 59 || second > 60 {
+        return None;
+    }
+    // The day is checked against the month and the year, so '31 Feb 2025' is kept as it is
+    let date = EntityExtractor::date(year, month as u32, day)?;
+    let offset = match parts.get(4).copied().unwrap_or("GMT") {
+        "GMT" | "UT" | "UTC" | "Z" => "+00:00",
+        "EDT" => "-04:00",
+        "EST" | "CDT" => "-05:00",
+        "CST" | "MDT" => "-06:00",
+        "MST" | "PDT" => "-07:00",
+        "PST" => "-08:00",
+        zone if zone.len() == 5 && zone.starts_with(['+', '-']) && zone[1..].chars().all(|ch| ch.is_ascii_digit()) => {
+            return Some(format!("{}T{:02}:{:02}:{:02}{}:{}", date, hour, minute, second, &zone[..3], &zone[3..]));
+        }
+        _ => return None,
+    };
+    Some(format!("{}T{:02}:{:02}:{:02}{}", date, hour, minute, second, offset))
+}
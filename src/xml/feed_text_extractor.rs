@@ -0,0 +1,170 @@
+use super::feed_parser::FeedParser;
+use crate::{error::Error, text_extractor::{ExtractParameters, TextExtractor}};
+use tokio::io::{AsyncRead, AsyncWrite};
+
+// Implementation of the TextExtractor trait for RSS and Atom feeds. The output contains a result for each item of the
+// feed with its title, link, published date, author and text, and the format, title and link of the feed.
+#[derive(Default)]
+pub struct FeedTextExtractor {}
+
+impl FeedTextExtractor {
+    pub fn new() -> Self {
+        Self::default()
+    }
+}
+
+impl TextExtractor for FeedTextExtractor {
+    async fn extract<R: AsyncRead + Unpin + Send, W: AsyncWrite + Unpin + Send>(
+        &mut self,
+        reader: &mut R,
+        writer: &mut W,
+        parameters: &ExtractParameters
+    ) -> Result<(), Error> {
+        FeedParser::new(reader, writer, parameters).parse().await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+
+    use super::FeedTextExtractor;
+    use crate::text_extractor::{ExtractParameters, TextExtractor};
+    use serde_json::json;
+
+    #[tokio::test]
+    async fn test_rss() {
+        let input = r#"<?xml version="1.0"?>
+<rss version="2.0" xmlns:content="http://purl.org/rss/1.0/modules/content/" xmlns:dc="http://purl.org/dc/elements/1.1/">
+<channel>
+  <title>Example News</title>
+  <link>https://example.com/</link>
+  <item>
+    <title>First post</title>
+    <link>https://example.com/first</link>
+    <pubDate>Tue, 10 Jun 2025 04:00:00 GMT</pubDate>
+    <dc:creator>Alice</dc:creator>
+    <description>Short &lt;b&gt;summary&lt;/b&gt;</description>
+    <content:encoded><![CDATA[<p>Full <em>text</em> of the post.</p><ul><li>One</li></ul>]]></content:encoded>
+  </item>
+  <item>
+    <title>Second post</title>
+    <pubDate>Wed, 11 Jun 2025 09:30 +0200</pubDate>
+    <description>&lt;p&gt;Only a description&lt;/p&gt;</description>
+  </item>
+  <item>
+    <title>Third post</title>
+    <pubDate>Fri, 31 Feb 2025 10:00:00 GMT</pubDate>
+    <description>No such day</description>
+  </item>
+</channel>
+</rss>"#;
+        let output = extract(input, &ExtractParameters::default()).await;
+        assert_eq!(output["results"], json!([
+            {
+                "title": "First post",
+                "link": "https://example.com/first",
+                "published": "2025-06-10T04:00:00+00:00",
+                "author": "Alice",
+                "text": "Full text of the post.\nOne"
+            },
+            { "title": "Second post", "published": "2025-06-11T09:30:00+02:00", "text": "Only a description" },
+            { "title": "Third post", "published": "Fri, 31 Feb 2025 10:00:00 GMT", "text": "No such day" }
+        ]));
+        assert_eq!(output["feed"], json!({ "format": "rss", "title": "Example News", "link": "https://example.com/" }));
+    }
+
+    #[tokio::test]
+    async fn test_rdf() {
+        let input = r#"<rdf:RDF xmlns:rdf="http://www.w3.org/1999/02/22-rdf-syntax-ns#" xmlns="http://purl.org/rss/1.0/"
+    xmlns:dc="http://purl.org/dc/elements/1.1/">
+  <channel rdf:about="https://example.org/"><title>Example</title><link>https://example.org/</link></channel>
+  <item rdf:about="https://example.org/a">
+    <title>Item A</title>
+    <link>https://example.org/a</link>
+    <dc:date>2024-02-29T12:00:00Z</dc:date>
+    <description>Text of A</description>
+  </item>
+</rdf:RDF>"#;
+        let output = extract(input, &ExtractParameters::default()).await;
+        assert_eq!(output["results"], json!([
+            { "title": "Item A", "link": "https://example.org/a", "published": "2024-02-29T12:00:00Z", "text": "Text of A" }
+        ]));
+        assert_eq!(output["feed"]["format"], "rdf");
+    }
+
+    #[tokio::test]
+    async fn test_atom() {
+        let input = r#"<feed xmlns="http://www.w3.org/2005/Atom">
+  <title>Atom Example</title>
+  <link rel="self" href="https://example.net/feed.xml"/>
+  <link href="https://example.net/"/>
+  <entry>
+    <title type="html">Tips &amp;amp; &lt;i&gt;tricks&lt;/i&gt;</title>
+    <link rel="alternate" href="https://example.net/tips"/>
+    <updated>2025-01-02T10:00:00Z</updated>
+    <author><name>Bob</name><email>bob@example.net</email></author>
+    <summary>Plain   summary</summary>
+  </entry>
+  <entry>
+    <title>XHTML entry</title>
+    <published>2025-01-01T08:00:00Z</published>
+    <content type="xhtml"><div xmlns="http://www.w3.org/1999/xhtml"><p>Para &amp; one</p><p>Para two</p></div></content>
+  </entry>
+</feed>"#;
+        let output = extract(input, &ExtractParameters::default()).await;
+        assert_eq!(output["results"], json!([
+            {
+                "title": "Tips & tricks",
+                "link": "https://example.net/tips",
+                "published": "2025-01-02T10:00:00Z",
+                "author": "Bob",
+                "text": "Plain summary"
+            },
+            { "title": "XHTML entry", "published": "2025-01-01T08:00:00Z", "text": "Para & one\nPara two" }
+        ]));
+        assert_eq!(output["feed"], json!({ "format": "atom", "title": "Atom Example", "link": "https://example.net/" }));
+
+        let output = extract("<html><body>Not a feed</body></html>", &ExtractParameters::default()).await;
+        assert_eq!(output["error"], "The document is not an RSS or Atom feed");
+    }
+
+    #[tokio::test]
+    async fn test_feed_redaction() {
+        let input = r#"<rss version="2.0"><channel><title>Call +36 30 123 4567</title>
+  <item>
+    <title>Mail jane.doe@example.com</title>
+    <link>https://example.com/profile?email=jane.doe@example.com</link>
+    <author>jane.doe@example.com (Jane Doe)</author>
+    <description>&lt;p&gt;Write to &lt;b&gt;jane.doe@example.com&lt;/b&gt;&lt;/p&gt;</description>
+  </item>
+</channel></rss>"#;
+        let parameters = ExtractParameters { redact: true, ..Default::default() };
+        let output = extract(input, &parameters).await;
+        assert_eq!(output["results"], json!([{
+            "title": "Mail [EMAIL]",
+            "link": "https://example.com/profile?email=[EMAIL]",
+            "author": "[EMAIL] (Jane Doe)",
+            "text": "Write to [EMAIL]"
+        }]));
+        assert_eq!(output["feed"]["title"], "Call [PHONE]");
+        assert_eq!(output["redactions"], json!({ "email": 4, "phone": 1, "iban": 0, "credit_card": 0, "ip_address": 0, "national_id": 0 }));
+
+        // Errors of the HTML content are reported on their items, and the items after them are returned as well
+        let input = "<rss version=\"2.0\"><channel><item><title>A</title><description>First</description></item>\
+            <item><title>B</title><description>&lt;p&gt;Broken &lt;a href=\"x</description></item>\
+            <item><title>C</title><description>Third</description></item></channel></rss>";
+        let output = extract(input, &ExtractParameters::default()).await;
+        assert_eq!(output["results"], json!([
+            { "title": "A", "text": "First" },
+            { "title": "B", "text": "", "error": "unexpected end of file" },
+            { "title": "C", "text": "Third" }
+        ]));
+        assert!(output["error"].is_null());
+    }
+
+    async fn extract(input: &str, parameters: &ExtractParameters) -> serde_json::Value {
+        let mut output = Vec::new();
+        FeedTextExtractor::new().extract(&mut input.as_bytes(), &mut output, parameters).await.unwrap();
+        serde_json::from_slice(&output).unwrap()
+    }
+}
@@ -0,0 +1,6 @@
+pub mod feed_text_extractor;
+pub mod xml_text_extractor;
+mod element_rules;
+mod feed_parser;
+mod xml_parser;
+mod xml_reader;
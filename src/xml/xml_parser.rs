@@ -0,0 +1,150 @@
+use tokio::io::{AsyncRead, AsyncWrite};
+
+use super::{element_rules::ElementRules, xml_reader::{XmlElement, XmlEvent, XmlReader}};
+use crate::{error::Error, text_extractor::ExtractParameters, util::{json_writer::JsonWriter, utf8_writer::Utf8Writer}};
+
+struct OpenElement {
+    output_enabled: bool,
+    excluded: bool,
+    // Whitespace is kept as it is (set by the xml:space attribute)
+    preserve_space: bool,
+    // Language declared by the xml:lang attribute
+    language: bool,
+    // Elements started after text of their parent (like emphasis in a paragraph) continue the text of the parent
+    inline: bool,
+}
+
+// Parser of well-formed XML documents writing the text content of the elements to the output. Each element not written
+// inside text is a separate text block.
+pub struct XmlParser<'a, R: AsyncRead + Unpin + Send, W: AsyncWrite + Unpin + Send> {
+    input: XmlReader<'a, R>,
+    writer: Utf8Writer<'a, W>,
+    output: JsonWriter,
+    rules: ElementRules,
+    elements: Vec<OpenElement>,
+    // Whether text was written since the last break between text blocks and since the start of the document
+    text_since_break: bool,
+    text_written: bool,
+}
+
+impl<'a, R: AsyncRead + Unpin + Send, W: AsyncWrite + Unpin + Send> XmlParser<'a, R, W> {
+    pub fn new(reader: &'a mut R, writer: &'a mut W, parameters: &ExtractParameters) -> Self {
+        Self {
+            input: XmlReader::new(reader),
+            writer: Utf8Writer::new(writer, 1024),
+            output: JsonWriter::new(parameters),
+            rules: ElementRules::new(parameters.include.as_deref(), parameters.exclude.as_deref()),
+            elements: Vec::new(),
+            text_since_break: false,
+            text_written: false,
+        }
+    }
+
+    pub async fn parse(&mut self) -> Result<(), Error> {
+        self.output.start();
+        let result = self.parse_content().await;
+        match result {
+            Ok(_) => self.output.end(""),
+            Err(error) => self.output.end(&error.message),
+        }
+        self.flush().await
+    }
+
+    async fn parse_content(&mut self) -> Result<(), Error> {
+        while let Some(event) = self.input.next().await? {
+            match event {
+                XmlEvent::Start(element) => self.start_element(&element),
+                XmlEvent::End(name) => self.end_element(&name),
+                XmlEvent::Text(text) => text.chars().for_each(|ch| self.write_text(ch)),
+            }
+            self.flush().await?;
+        }
+        Ok(())
+    }
+
+    fn start_element(&mut self, element: &XmlElement) {
+        let (parent_enabled, parent_excluded, parent_preserve) = self
+            .elements
+            .last()
+            .map_or((self.rules.includes_root(), false, false), |parent| (parent.output_enabled, parent.excluded, parent.preserve_space));
+
+        // Content of excluded elements is not included by include rules
+        let excluded = parent_excluded || self.rules.is_excluded(element);
+        let output_enabled = !excluded && (parent_enabled || self.rules.is_included(element));
+        let preserve_space = match element.attribute("xml:space") {
+            Some("preserve") => true,
+            Some("default") => false,
+            _ => parent_preserve,
+        };
+        let language = element.attribute("xml:lang");
+
+        let inline = self.text_since_break;
+        if !inline {
+            self.add_break(&format!("<{}>", element.name));
+        }
+        if preserve_space && !parent_preserve {
+            self.output.start_preformatted(false);
+        }
+        if let Some(language) = language {
+            self.output.start_language(language);
+        }
+        self.elements.push(OpenElement { output_enabled, excluded, preserve_space, language: language.is_some(), inline });
+    }
+
+    fn end_element(&mut self, name: &str) {
+        let Some(element) = self.elements.pop() else {
+            return;
+        };
+        if element.language {
+            self.output.end_language();
+        }
+        let parent_preserve = self.elements.last().is_some_and(|parent| parent.preserve_space);
+        if element.preserve_space && !parent_preserve {
+            self.output.end_preformatted();
+        }
+        if !element.inline {
+            self.add_break(&format!("</{}>", name));
+        }
+    }
+
+    // Elements before the first text only set the separator of the first text block (so no empty block is written
+    // for the root element)
+    fn add_break(&mut self, separator: &str) {
+        if self.text_written {
+            self.output.add_break(separator);
+        } else {
+            self.output.fragment_mut().separator = String::from(separator);
+        }
+        self.text_since_break = false;
+    }
+
+    fn write_text(&mut self, ch: char) {
+        let Some(element) = self.elements.last() else {
+            return;
+        };
+        if !ch.is_whitespace() {
+            self.text_since_break = true;
+        }
+        if !element.output_enabled {
+            return;
+        }
+        if !ch.is_whitespace() {
+            self.text_written = true;
+        }
+        if self.output.is_preformatted() {
+            if ch != '\r' {
+                self.output.write_char(ch);
+            }
+        } else if ch.is_whitespace() {
+            self.output.write_char(' ');
+        } else {
+            self.output.write_char(ch);
+        }
+    }
+
+    // Write the JSON serialized by the output to the output stream
+    async fn flush(&mut self) -> Result<(), Error> {
+        let json = self.output.take_output();
+        self.writer.write_string(&json).await
+    }
+}
@@ -0,0 +1,434 @@
+use std::collections::HashMap;
+use tokio::io::AsyncRead;
+
+use crate::{error::Error, util::utf8_reader::Utf8Reader};
+
+// Entities declared in the DTD can refer to each other, so their expansion is limited to avoid exponential growth
+// (predefined entities and character references are not counted)
+const MAX_ENTITY_DEPTH: usize = 16;
+const MAX_ENTITY_EXPANSION: usize = 1_000_000;
+// Longer references are not entity references, they are read as text
+const MAX_ENTITY_NAME_LENGTH: usize = 64;
+// Long texts are returned in parts, so they are not kept in memory
+const MAX_TEXT_LENGTH: usize = 1024;
+
+pub struct XmlElement {
+    // Name written in the document (with the prefix of the namespace)
+    pub name: String,
+    // URI of the namespace of the element (None if it is not in a namespace)
+    pub namespace: Option<String>,
+    pub attributes: Vec<(String, String)>,
+}
+
+impl XmlElement {
+    pub fn local_name(&self) -> &str {
+        self.name.rsplit(':').next().unwrap_or(&self.name)
+    }
+
+    pub fn attribute(&self, name: &str) -> Option<&str> {
+        self.attributes.iter().find(|(attribute, _)| attribute == name).map(|(_, value)| value.as_str())
+    }
+}
+
+pub enum XmlEvent {
+    Start(XmlElement),
+    End(String),
+    // Character data of the elements (references are replaced and CDATA sections are included)
+    Text(String),
+}
+
+// Streaming reader of well-formed XML documents returning the elements and texts of the document. Processing
+// instructions and comments are skipped, and entities declared in the internal subset of the DTD are expanded.
+pub struct XmlReader<'a, R: AsyncRead + Unpin + Send> {
+    input: Utf8Reader<'a, R>,
+    // Character read ahead and not processed yet
+    pending: Option<char>,
+    // End of an empty element not returned yet
+    pending_end: Option<String>,
+    text: String,
+    // Entities declared in the internal subset of the DTD
+    entities: HashMap<String, String>,
+    // Length of the replacement texts of the declared entities expanded so far
+    expanded_length: usize,
+    // Names of the open elements and the namespaces declared by them (the default namespace has an empty prefix)
+    elements: Vec<(String, Vec<(String, String)>)>,
+}
+
+impl<'a, R: AsyncRead + Unpin + Send> XmlReader<'a, R> {
+    pub fn new(reader: &'a mut R) -> Self {
+        Self {
+            input: Utf8Reader::new(reader),
+            pending: None,
+            pending_end: None,
+            text: String::new(),
+            entities: HashMap::new(),
+            expanded_length: 0,
+            elements: Vec::new(),
+        }
+    }
+
+    // Return the next event of the document (None at the end of the document)
+    pub async fn next(&mut self) -> Result<Option<XmlEvent>, Error> {
+        if let Some(name) = self.pending_end.take() {
+            self.elements.pop();
+            return Ok(Some(XmlEvent::End(name)));
+        }
+        loop {
+            let ch = match self.read().await {
+                Ok(ch) => ch,
+                Err(error) if error.eof && self.elements.is_empty() => return Ok(None),
+                Err(error) => return Err(error),
+            };
+            match ch {
+                '<' if !self.text.is_empty() => {
+                    self.pending = Some(ch);
+                    return Ok(Some(XmlEvent::Text(std::mem::take(&mut self.text))));
+                }
+                '<' => {
+                    if let Some(event) = self.parse_markup().await? {
+                        return Ok(Some(event));
+                    }
+                }
+                '&' => {
+                    let text = self.parse_reference().await?;
+                    self.push_text(&text);
+                }
+                // Text outside of the root element can only be whitespace
+                ch if !self.elements.is_empty() => {
+                    self.text.push(ch);
+                    if self.text.len() >= MAX_TEXT_LENGTH {
+                        return Ok(Some(XmlEvent::Text(std::mem::take(&mut self.text))));
+                    }
+                }
+                _ => {}
+            }
+        }
+    }
+
+    fn push_text(&mut self, text: &str) {
+        if !self.elements.is_empty() {
+            self.text.push_str(text);
+        }
+    }
+
+    async fn parse_markup(&mut self) -> Result<Option<XmlEvent>, Error> {
+        match self.read().await? {
+            // Processing instructions (including the XML declaration) contain no text
+            '?' => self.skip_until("?>").await?,
+            '!' => {
+                let ch = self.read().await?;
+                if ch == '-' {
+                    self.expect("-").await?;
+                    self.skip_until("-->").await?;
+                } else if ch == '[' {
+                    self.expect("CDATA[").await?;
+                    self.parse_cdata().await?;
+                } else {
+                    self.pending = Some(ch);
+                    self.expect("DOCTYPE").await?;
+                    self.parse_doctype().await?;
+                }
+            }
+            '/' => {
+                let name = self.read_until(|ch| ch == '>').await?;
+                self.read().await?;
+                let name = name.trim();
+                return match self.elements.pop() {
+                    Some((open, _)) if open == name => Ok(Some(XmlEvent::End(open))),
+                    _ => Err(parse_error(&format!("Unexpected end tag: {}", name))),
+                };
+            }
+            ch => {
+                self.pending = Some(ch);
+                return self.parse_start_tag().await.map(|element| Some(XmlEvent::Start(element)));
+            }
+        }
+        Ok(None)
+    }
+
+    async fn parse_start_tag(&mut self) -> Result<XmlElement, Error> {
+        let name = self.read_until(|ch| ch.is_whitespace() || ch == '/' || ch == '>').await?;
+        let mut attributes: Vec<(String, String)> = Vec::new();
+        let is_empty = loop {
+            match self.read_non_whitespace().await? {
+                '>' => break false,
+                '/' => {
+                    self.expect(">").await?;
+                    break true;
+                }
+                ch => {
+                    self.pending = Some(ch);
+                    let attribute_name = self.read_until(|ch| ch == '=' || ch.is_whitespace()).await?;
+                    if self.read_non_whitespace().await? != '=' {
+                        return Err(parse_error(&format!("Missing value of attribute {}", attribute_name)));
+                    }
+                    let value = self.parse_attribute_value().await?;
+                    attributes.push((attribute_name, value));
+                }
+            }
+        };
+
+        let namespaces: Vec<(String, String)> = attributes
+            .iter()
+            .filter_map(|(attribute, value)| match attribute.as_str() {
+                "xmlns" => Some((String::new(), value.clone())),
+                _ => attribute.strip_prefix("xmlns:").map(|prefix| (String::from(prefix), value.clone())),
+            })
+            .collect();
+        let prefix = name.split_once(':').map_or("", |(prefix, _)| prefix);
+        let namespace = namespaces
+            .iter()
+            .chain(self.elements.iter().rev().flat_map(|(_, namespaces)| namespaces.iter()))
+            .find(|(declared_prefix, _)| declared_prefix == prefix)
+            .map(|(_, uri)| uri.clone())
+            .filter(|uri| !uri.is_empty());
+
+        self.elements.push((name.clone(), namespaces));
+        if is_empty {
+            self.pending_end = Some(name.clone());
+        }
+        Ok(XmlElement { name, namespace, attributes })
+    }
+
+    async fn parse_attribute_value(&mut self) -> Result<String, Error> {
+        let quote = self.read_non_whitespace().await?;
+        if quote != '"' && quote != '\'' {
+            return Err(parse_error("Attribute values must be quoted"));
+        }
+        let mut value = String::new();
+        loop {
+            match self.read().await? {
+                ch if ch == quote => return Ok(value),
+                '&' => value.push_str(&self.parse_reference().await?),
+                ch if ch.is_whitespace() => value.push(' '),
+                ch => value.push(ch),
+            }
+        }
+    }
+
+    // Text of CDATA sections is read without parsing markup and references
+    async fn parse_cdata(&mut self) -> Result<(), Error> {
+        let mut brackets = 0;
+        loop {
+            let ch = self.read().await?;
+            match ch {
+                ']' => brackets += 1,
+                '>' if brackets >= 2 => {
+                    self.push_text(&"]".repeat(brackets - 2));
+                    return Ok(());
+                }
+                ch => {
+                    self.push_text(&"]".repeat(brackets));
+                    brackets = 0;
+                    self.push_text(ch.encode_utf8(&mut [0; 4]));
+                }
+            }
+        }
+    }
+
+    // Read the entity declarations of the internal subset of the DTD (external entities are not loaded)
+    async fn parse_doctype(&mut self) -> Result<(), Error> {
+        loop {
+            match self.read().await? {
+                '>' => return Ok(()),
+                '"' => self.skip_until("\"").await?,
+                '\'' => self.skip_until("'").await?,
+                '[' => break,
+                _ => {}
+            }
+        }
+        loop {
+            match self.read_non_whitespace().await? {
+                ']' => {
+                    self.skip_until(">").await?;
+                    return Ok(());
+                }
+                '%' => self.skip_until(";").await?,
+                '<' => match self.read().await? {
+                    '?' => self.skip_until("?>").await?,
+                    '!' => {
+                        let keyword = self.read_until(|ch| ch.is_whitespace() || ch == '>' || ch == '-').await?;
+                        match keyword.as_str() {
+                            "" => {
+                                self.expect("--").await?;
+                                self.skip_until("-->").await?;
+                            }
+                            "ENTITY" => self.parse_entity_declaration().await?,
+                            _ => self.skip_declaration().await?,
+                        }
+                    }
+                    _ => return Err(parse_error("Invalid markup in DTD")),
+                },
+                _ => return Err(parse_error("Invalid markup in DTD")),
+            }
+        }
+    }
+
+    async fn parse_entity_declaration(&mut self) -> Result<(), Error> {
+        let ch = self.read_non_whitespace().await?;
+        // Parameter entities are only used inside the DTD
+        if ch == '%' {
+            return self.skip_declaration().await;
+        }
+        self.pending = Some(ch);
+        let name = self.read_until(char::is_whitespace).await?;
+        let quote = self.read_non_whitespace().await?;
+        if quote != '"' && quote != '\'' {
+            // External entities are declared with a system or public identifier
+            self.pending = Some(quote);
+            return self.skip_declaration().await;
+        }
+        let value = self.read_until(|ch| ch == quote).await?;
+        self.read().await?;
+        self.skip_declaration().await?;
+        self.entities.entry(name).or_insert(value);
+        Ok(())
+    }
+
+    // Skip the rest of a declaration (quoted literals can contain '>')
+    async fn skip_declaration(&mut self) -> Result<(), Error> {
+        loop {
+            match self.read().await? {
+                '>' => return Ok(()),
+                '"' => self.skip_until("\"").await?,
+                '\'' => self.skip_until("'").await?,
+                _ => {}
+            }
+        }
+    }
+
+    // Read a character or entity reference (after '&') and return its text
+    async fn parse_reference(&mut self) -> Result<String, Error> {
+        let mut name = String::new();
+        loop {
+            let ch = self.read().await?;
+            if ch == ';' {
+                break;
+            }
+            if ch.is_whitespace() || ch == '<' || ch == '&' || name.len() >= MAX_ENTITY_NAME_LENGTH {
+                self.pending = Some(ch);
+                return Ok(format!("&{}", name));
+            }
+            name.push(ch);
+        }
+        self.expand(&name, 0).ok_or_else(|| parse_error(&format!("Undeclared entity: {}", name)))?
+    }
+
+    fn expand(&mut self, name: &str, depth: usize) -> Option<Result<String, Error>> {
+        let text = match name {
+            "amp" => String::from("&"),
+            "lt" => String::from("<"),
+            "gt" => String::from(">"),
+            "quot" => String::from("\""),
+            "apos" => String::from("'"),
+            _ if name.starts_with('#') => {
+                let code = match name.strip_prefix("#x").or_else(|| name.strip_prefix("#X")) {
+                    Some(hex) => u32::from_str_radix(hex, 16).ok(),
+                    None => name[1..].parse().ok(),
+                };
+                String::from(char::from_u32(code?)?)
+            }
+            _ => {
+                let value = self.entities.get(name)?.clone();
+                if depth >= MAX_ENTITY_DEPTH {
+                    return Some(Err(parse_error("Entities are nested too deeply")));
+                }
+                // References in the replacement text of declared entities are expanded as well
+                let mut text = String::new();
+                let mut rest = value.as_str();
+                while let Some(start) = rest.find('&') {
+                    text.push_str(&rest[..start]);
+                    match rest[start..].find(';') {
+                        Some(end) => {
+                            match self.expand(&rest[start + 1..start + end], depth + 1) {
+                                Some(Ok(expanded)) => {
+                                    // Partial texts are checked as well, so nested entities can't grow beyond the limit
+                                    text.push_str(&expanded);
+                                    if self.expanded_length + text.len() > MAX_ENTITY_EXPANSION {
+                                        return Some(Err(parse_error("Entity expansion limit exceeded")));
+                                    }
+                                }
+                                Some(Err(error)) => return Some(Err(error)),
+                                None => text.push_str(&rest[start..=start + end]),
+                            }
+                            rest = &rest[start + end + 1..];
+                        }
+                        None => {
+                            text.push_str(&rest[start..]);
+                            rest = "";
+                        }
+                    }
+                }
+                text.push_str(rest);
+
+                // Replacement texts of the declared entities referenced in the document are counted once (the texts
+                // of the nested entities are part of them)
+                if depth == 0 {
+                    self.expanded_length += text.len();
+                    if self.expanded_length > MAX_ENTITY_EXPANSION {
+                        return Some(Err(parse_error("Entity expansion limit exceeded")));
+                    }
+                }
+                text
+            }
+        };
+        Some(Ok(text))
+    }
+
+    async fn read(&mut self) -> Result<char, Error> {
+        match self.pending.take() {
+            Some(ch) => Ok(ch),
+            None => self.input.read_char().await,
+        }
+    }
+
+    async fn read_non_whitespace(&mut self) -> Result<char, Error> {
+        loop {
+            let ch = self.read().await?;
+            if !ch.is_whitespace() {
+                return Ok(ch);
+            }
+        }
+    }
+
+    // Read characters until the first one matching the condition, which is not consumed
+    async fn read_until(&mut self, condition: impl Fn(char) -> bool) -> Result<String, Error> {
+        let mut text = String::new();
+        loop {
+            let ch = self.read().await?;
+            if condition(ch) {
+                self.pending = Some(ch);
+                return Ok(text);
+            }
+            text.push(ch);
+        }
+    }
+
+    // Skip characters until the end of a text (which is consumed)
+    async fn skip_until(&mut self, end: &str) -> Result<(), Error> {
+        let end: Vec<char> = end.chars().collect();
+        let mut last: Vec<char> = Vec::with_capacity(end.len());
+        loop {
+            if last.len() == end.len() {
+                last.remove(0);
+            }
+            last.push(self.read().await?);
+            if last == end {
+                return Ok(());
+            }
+        }
+    }
+
+    async fn expect(&mut self, text: &str) -> Result<(), Error> {
+        for expected in text.chars() {
+            if self.read().await? != expected {
+                return Err(parse_error(&format!("Expected {}", text)));
+            }
+        }
+        Ok(())
+    }
+}
+
+fn parse_error(message: &str) -> Error {
+    Error { eof: false, message: String::from(message) }
+}
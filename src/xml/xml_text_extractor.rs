@@ -0,0 +1,121 @@
+use super::xml_parser::XmlParser;
+use crate::{error::Error, text_extractor::{ExtractParameters, TextExtractor}};
+use tokio::io::{AsyncRead, AsyncWrite};
+
+// Implementation of the TextExtractor trait for the 'xml' file format. The include and exclude parameters contain names
+// of elements instead of CSS selectors.
+#[derive(Default)]
+pub struct XmlTextExtractor {}
+
+impl XmlTextExtractor {
+    pub fn new() -> Self {
+        Self::default()
+    }
+}
+
+impl TextExtractor for XmlTextExtractor {
+    async fn extract<R: AsyncRead + Unpin + Send, W: AsyncWrite + Unpin + Send>(
+        &mut self,
+        reader: &mut R,
+        writer: &mut W,
+        parameters: &ExtractParameters
+    ) -> Result<(), Error> {
+        XmlParser::new(reader, writer, parameters).parse().await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+
+    use super::XmlTextExtractor;
+    use crate::text_extractor::{ExtractParameters, OutputFormat, TextExtractor};
+
+    #[tokio::test]
+    async fn test_xml() {
+        let input = r#"<?xml version="1.0" encoding="UTF-8"?>
+<!-- Exported catalog -->
+<catalog xmlns="http://example.com/catalog">
+  <book id="1">
+    <title>Rust &amp; XML</title>
+    <description>A <em>short</em> guide &#x2014; with examples.</description>
+  </book>
+  <?render page-break?>
+  <note><![CDATA[Use <tags> & entities]]></note>
+</catalog>"#;
+        let output = extract(input, &ExtractParameters::default()).await;
+        assert_eq!(output["results"][0]["text"], "Rust & XML\nA short guide — with examples.\nUse <tags> & entities");
+        assert!(output["error"].is_null());
+    }
+
+    #[tokio::test]
+    async fn test_xml_dtd_entities() {
+        let input = r#"<!DOCTYPE doc [
+  <!ENTITY company "Example Ltd.">
+  <!ENTITY signature "Regards, &company;">
+  <!ELEMENT doc (#PCDATA)>
+]>
+<doc>&signature;</doc>"#;
+        let output = extract(input, &ExtractParameters::default()).await;
+        assert_eq!(output["results"][0]["text"], "Regards, Example Ltd.");
+
+        // Expansion of recursive entities is limited
+        let input = r#"<!DOCTYPE doc [
+  <!ENTITY a "&b;&b;">
+  <!ENTITY b "&a;&a;">
+]>
+<doc>&a;</doc>"#;
+        let output = extract(input, &ExtractParameters::default()).await;
+        assert_eq!(output["error"], "Entities are nested too deeply");
+
+        let output = extract("<doc>&unknown;</doc>", &ExtractParameters::default()).await;
+        assert_eq!(output["error"], "Undeclared entity: unknown");
+
+        // Only the replacement texts of the declared entities are limited, escaped data can be of any length
+        let input = format!("<doc>{}</doc>", "&amp;&#65;&lt;".repeat(400_000));
+        let output = extract(&input, &ExtractParameters::default()).await;
+        assert!(output["error"].is_null());
+        assert_eq!(output["results"][0]["text"].as_str().unwrap().len(), 1_200_000);
+
+        let input = format!("<!DOCTYPE doc [\n  <!ENTITY a \"{}\">\n  <!ENTITY b \"{}\">\n]>\n<doc>{}</doc>", "x".repeat(1000), "&a;".repeat(100), "&b;".repeat(11));
+        let output = extract(&input, &ExtractParameters::default()).await;
+        assert_eq!(output["error"], "Entity expansion limit exceeded");
+    }
+
+    #[tokio::test]
+    async fn test_xml_space() {
+        let input = "<doc><p>a   b</p><code xml:space=\"preserve\">fn main() {\n    run();\n}</code></doc>";
+        let parameters = ExtractParameters { output_format: OutputFormat::Advanced, ..Default::default() };
+        let output = extract(input, &parameters).await;
+        assert_eq!(output["results"][0]["text"], "a b");
+        assert_eq!(output["results"][1]["text"], "fn main() {\n    run();\n}");
+    }
+
+    #[tokio::test]
+    async fn test_xml_element_rules() {
+        let input = r#"<rss xmlns:dc="http://purl.org/dc/elements/1.1/">
+<item><title>First</title><dc:creator>Alice</dc:creator><guid>1</guid></item>
+<item><title>Second</title><dc:creator>Bob</dc:creator><guid>2</guid></item>
+</rss>"#;
+        let parameters = ExtractParameters {
+            output_format: OutputFormat::Advanced,
+            include: Some(String::from("title, {http://purl.org/dc/elements/1.1/}creator")),
+            ..Default::default()
+        };
+        let output = extract(input, &parameters).await;
+        let texts: Vec<&str> = output["results"].as_array().unwrap().iter().filter_map(|result| result["text"].as_str()).collect();
+        assert_eq!(texts, vec!["First", "Alice", "Second", "Bob"]);
+
+        let parameters = ExtractParameters { exclude: Some(String::from("dc:creator,guid")), ..Default::default() };
+        let output = extract(input, &parameters).await;
+        assert_eq!(output["results"][0]["text"], "First\nSecond");
+
+        let output = extract("<doc><a>text</b></doc>", &ExtractParameters::default()).await;
+        assert_eq!(output["error"], "Unexpected end tag: b");
+    }
+
+    async fn extract(input: &str, parameters: &ExtractParameters) -> serde_json::Value {
+        let mut output = Vec::new();
+        XmlTextExtractor::new().extract(&mut input.as_bytes(), &mut output, parameters).await.unwrap();
+        serde_json::from_slice(&output).unwrap()
+    }
+}